@@ -0,0 +1,74 @@
+#!/usr/bin/env bash
+# Compare the single-pass history collection against the previous approach of one
+# `git log --follow` call per changeset path on a synthetic repository.
+#
+#   scripts/bench-history.sh [changesets] [batch]
+#
+# The repository gets one commit per changeset and a release commit deleting every `batch`
+# changesets (1000 and 10 by default, 1101 commits). Set KEEP=1 to keep it for inspection.
+set -euo pipefail
+
+changesets=${1:-1000}
+batch=${2:-10}
+root=$(cd "$(dirname "$0")/.." && pwd)
+repo=$(mktemp -d)
+if [[ -z ${KEEP:-} ]]; then
+    trap 'rm -rf "$repo"' EXIT
+fi
+
+generate() {
+    local time=1704067200 # 2024-01-01T00:00:00Z
+    local mark=0
+    commit() {
+        mark=$((mark + 1))
+        time=$((time + 3600))
+        printf 'commit refs/heads/master\nmark :%d\n' "$mark"
+        printf 'author Bench <bench@example.com> %d +0000\n' "$time"
+        printf 'committer Bench <bench@example.com> %d +0000\n' "$time"
+        printf 'data %d\n%s\n' "${#1}" "$1"
+        if ((mark > 1)); then
+            printf 'from :%d\n' $((mark - 1))
+        fi
+    }
+    commit "init"
+    printf 'M 644 inline README.md\ndata 6\nbench\n\n'
+    for ((i = 1; i <= changesets; i++)); do
+        local content
+        content=$(printf -- '---\n"pkg-%d": patch\n---\n\nChange %d\n' $((i % 7)) "$i")
+        commit "change $i"
+        printf 'M 644 inline .changeset/change-%d.md\ndata %d\n%s\n\n' "$i" "${#content}" "$content"
+        if ((i % batch == 0)); then
+            commit "release $((i / batch))"
+            for ((j = i - batch + 1; j <= i; j++)); do
+                printf 'D .changeset/change-%d.md\n' "$j"
+            done
+            printf '\n'
+        fi
+    done
+}
+
+git -C "$repo" init -q -b master
+generate | git -C "$repo" fast-import --quiet
+git -C "$repo" reset -q --hard master
+echo "$(git -C "$repo" rev-list --count master) commits, $changesets changesets in $repo"
+
+cargo build --quiet --release --manifest-path "$root/Cargo.toml"
+bin="$root/target/release/changeset_lifetime"
+
+# What the tool did before: list the paths, then one `git log --follow` of the adds and
+# deletes of every path
+per_path() {
+    git -C "$repo" log master --diff-filter=AD --name-only --pretty=format: -- .changeset |
+        sort -u |
+        while read -r path; do
+            [[ -n $path ]] || continue
+            git -C "$repo" log master --topo-order --diff-filter=AD --follow --name-status \
+                --format='%H %aI' -- "$path" >/dev/null
+        done
+}
+
+TIMEFORMAT='%3Rs'
+echo -n "per-path git log: "
+time per_path
+echo -n "single pass:      "
+time "$bin" -d "$repo" report --start 2024-01-01T00:00:00Z --end 2030-01-01T00:00:00Z --days 0 >/dev/null
@@ -0,0 +1,51 @@
+use crate::error::Result;
+use crate::history::Event;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+/// Extent of the history available in a shallow clone.
+pub struct Shallow {
+    /// Number of commits reachable from the branch.
+    pub depth: usize,
+    /// Committer date of the oldest reachable commit.
+    pub oldest: DateTime<Utc>,
+}
+
+/// A tag and its creation date (the commit date for lightweight tags).
+#[derive(Debug, Clone)]
+pub struct Tag {
+    pub name: String,
+    pub date: DateTime<Utc>,
+}
+
+/// Source of repository history for the analysis.
+///
+/// [`crate::git::GitCli`] shells out to the `git` binary; `crate::gitoxide::Gitoxide`, built
+/// with the `gix` feature, reads the object database in process.
+pub trait Backend {
+    /// Every add and delete under any of `dirs` on `branch`, keyed by path. `dirs` are
+    /// relative to the repository root and may be globs. Renames are reported as a delete of
+    /// the old path and an add of the new one. Only paths accepted by `keep` are collected.
+    ///
+    /// The events of a path are in history order, oldest first: a commit always comes after
+    /// its parents, whatever its date.
+    fn history_events(
+        &mut self,
+        branch: &str,
+        dirs: &[String],
+        keep: &dyn Fn(&str) -> bool,
+    ) -> Result<BTreeMap<String, Vec<Event>>>;
+
+    /// Content of `path` as of `commit`, or `None` if it does not exist there.
+    fn read_file(&mut self, commit: &str, path: &str) -> Result<Option<String>>;
+
+    /// `None` if the full history is available, otherwise how much of `branch` is present.
+    fn shallow(&mut self, branch: &str) -> Result<Option<Shallow>>;
+
+    /// Number of commits on `branch` from its tip back to and including `commit`. Fetching
+    /// this many commits is enough to include `commit`.
+    fn depth_of(&mut self, branch: &str, commit: &str) -> Result<usize>;
+
+    /// Earliest created tag containing `commit`, restricted to tags matching the glob `pattern`.
+    fn first_tag_containing(&mut self, commit: &str, pattern: Option<&str>) -> Result<Option<Tag>>;
+}
@@ -0,0 +1,35 @@
+use crate::glob::Glob;
+
+/// Files the Changesets tool keeps in its directory that are never changesets themselves.
+const TOOL_FILES: [&str; 3] = ["README.md", "config.json", "pre.json"];
+
+/// Decides which paths under the changeset directory are changesets.
+pub struct Classifier {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+impl Classifier {
+    /// Without include patterns every markdown file is a candidate.
+    pub fn new(include: &[Glob], exclude: &[Glob]) -> Classifier {
+        let include = match include.is_empty() {
+            true => vec![Glob::new("*.md")],
+            false => include.to_vec(),
+        };
+        Classifier {
+            include,
+            exclude: exclude.to_vec(),
+        }
+    }
+
+    /// Path based check, done before any history is looked up. A candidate still needs valid
+    /// frontmatter to count as a changeset.
+    pub fn is_candidate(&self, path: &str) -> bool {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        if TOOL_FILES.iter().any(|f| f.eq_ignore_ascii_case(name)) {
+            return false;
+        }
+        self.include.iter().any(|g| g.matches(path))
+            && !self.exclude.iter().any(|g| g.matches(path))
+    }
+}
@@ -0,0 +1,45 @@
+use crate::ChangesetLifetime;
+use crate::stats::{Stat, Summary};
+use chrono::{DateTime, Duration, Utc};
+
+/// Statistics of one analysis window.
+pub struct Period {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub summary: Summary,
+    /// Changesets older than the comparison threshold.
+    pub over_threshold: usize,
+}
+
+impl Period {
+    pub fn new(
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        changesets: &[ChangesetLifetime],
+        threshold: Duration,
+        stats: &[Stat],
+    ) -> Period {
+        let ages: Vec<Duration> = changesets.iter().map(|cs| cs.age).collect();
+        Period {
+            start,
+            end,
+            summary: Summary::from_ages(&ages, stats),
+            over_threshold: ages.iter().filter(|age| **age > threshold).count(),
+        }
+    }
+
+    /// Share of changesets older than the threshold, between 0 and 1.
+    pub fn over_threshold_share(&self) -> f64 {
+        match self.summary.count {
+            0 => 0.0,
+            n => self.over_threshold as f64 / n as f64,
+        }
+    }
+}
+
+/// Two windows analyzed with the same options; deltas are `current - baseline`.
+pub struct Comparison {
+    pub threshold: Duration,
+    pub baseline: Period,
+    pub current: Period,
+}
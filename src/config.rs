@@ -0,0 +1,216 @@
+use crate::error::{Error, Result};
+use crate::glob::Glob;
+use crate::group::GroupBy;
+use crate::output::OutputFormat;
+use chrono::Duration;
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde::de::{Deserializer, Error as _};
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file looked up at the repository root.
+pub const FILE_NAME: &str = "changeset-lifetime.toml";
+
+/// Defaults read from `changeset-lifetime.toml`; command line flags take precedence.
+///
+/// ```toml
+/// branch = "main"
+/// changeset_dir = ["packages/*/.changeset"]
+/// include = ["*.md"]
+/// exclude = ["drafts/*"]
+/// format = "json"
+/// group_by = "package"
+/// allow_shallow = true
+///
+/// [thresholds]
+/// days = "30days"     # report --days, and stats --days with a baseline
+/// max_age = "14days"  # check --max-age
+/// ```
+#[derive(Default)]
+pub struct Config {
+    pub branch: Option<String>,
+    pub changeset_dirs: Vec<String>,
+    pub include: Vec<Glob>,
+    pub exclude: Vec<Glob>,
+    pub format: Option<OutputFormat>,
+    pub group_by: Option<GroupBy>,
+    pub allow_shallow: Option<bool>,
+    pub min_age: Option<Duration>,
+    pub max_age: Option<Duration>,
+}
+
+/// Root of the repository containing `dir`, found by looking for `.git` upwards.
+pub fn repo_root(dir: &str) -> Option<PathBuf> {
+    let dir = Path::new(dir).canonicalize().ok()?;
+    let root = dir.ancestors().find(|d| d.join(".git").exists())?;
+    Some(root.to_path_buf())
+}
+
+/// The configuration file at the root of the repository containing `dir`, if there is one.
+pub fn discover(dir: &str) -> Option<PathBuf> {
+    let path = repo_root(dir)?.join(FILE_NAME);
+    path.is_file().then_some(path)
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config> {
+        let text = std::fs::read_to_string(path).map_err(|error| Error::ConfigRead {
+            path: path.display().to_string(),
+            error,
+        })?;
+        Config::parse(&text).map_err(|(line, message)| Error::Config {
+            path: path.display().to_string(),
+            line,
+            message,
+        })
+    }
+
+    /// Errors carry the 1-based line they were found on.
+    fn parse(text: &str) -> std::result::Result<Config, (usize, String)> {
+        let file: File = toml::from_str(text).map_err(|e| {
+            let offset = e.span().map_or(0, |span| span.start);
+            let line = text[..offset].matches('\n').count() + 1;
+            (line, e.message().to_string())
+        })?;
+        Ok(Config {
+            branch: file.branch,
+            changeset_dirs: file.changeset_dir,
+            include: file.include.iter().map(|s| Glob::new(s)).collect(),
+            exclude: file.exclude.iter().map(|s| Glob::new(s)).collect(),
+            format: file.format,
+            group_by: file.group_by,
+            allow_shallow: file.allow_shallow,
+            min_age: file.thresholds.days,
+            max_age: file.thresholds.max_age,
+        })
+    }
+}
+
+/// `changeset-lifetime.toml` as written.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    branch: Option<String>,
+    #[serde(default, deserialize_with = "strings")]
+    changeset_dir: Vec<String>,
+    #[serde(default, deserialize_with = "strings")]
+    include: Vec<String>,
+    #[serde(default, deserialize_with = "strings")]
+    exclude: Vec<String>,
+    #[serde(default, deserialize_with = "value_enum")]
+    format: Option<OutputFormat>,
+    #[serde(default, deserialize_with = "value_enum")]
+    group_by: Option<GroupBy>,
+    allow_shallow: Option<bool>,
+    #[serde(default)]
+    thresholds: Thresholds,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Thresholds {
+    #[serde(default, deserialize_with = "duration")]
+    days: Option<Duration>,
+    #[serde(default, deserialize_with = "duration")]
+    max_age: Option<Duration>,
+}
+
+/// A single string or an array of them.
+fn strings<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged, expecting = "a string or an array of strings")]
+    enum Strings {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match Strings::deserialize(d)? {
+        Strings::One(s) => vec![s],
+        Strings::Many(s) => s,
+    })
+}
+
+fn value_enum<'de, D: Deserializer<'de>, T: ValueEnum>(
+    d: D,
+) -> std::result::Result<Option<T>, D::Error> {
+    let s = String::deserialize(d)?;
+    T::from_str(&s, true).map(Some).map_err(|_| {
+        let names: Vec<String> = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        D::Error::custom(format!(
+            "invalid value {s:?}, expected one of {}",
+            names.join(", ")
+        ))
+    })
+}
+
+/// A humantime duration such as `"30days"`, or a number of days.
+fn duration<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Option<Duration>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(
+        untagged,
+        expecting = "a duration such as \"30days\" or a number of days"
+    )]
+    enum Value {
+        Days(i64),
+        Text(String),
+    }
+    let duration = match Value::deserialize(d)? {
+        Value::Days(days) => Duration::try_days(days),
+        Value::Text(s) => {
+            let std = humantime::parse_duration(&s)
+                .map_err(|e| D::Error::custom(format!("invalid duration {s:?}: {e}")))?;
+            Duration::from_std(std).ok()
+        }
+    };
+    duration
+        .map(Some)
+        .ok_or_else(|| D::Error::custom("duration is too long"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_every_setting() {
+        let text = r#"
+branch = "main"
+changeset_dir = "packages/*/.changeset"
+include = ["*.md"]
+format = "JSON"
+allow_shallow = true
+
+[thresholds]
+days = 3
+max_age = "2weeks"
+"#;
+        let config = Config::parse(text).ok().unwrap();
+        assert_eq!(config.branch.as_deref(), Some("main"));
+        assert_eq!(config.changeset_dirs, ["packages/*/.changeset"]);
+        assert_eq!(config.include.len(), 1);
+        assert!(config.exclude.is_empty());
+        assert!(matches!(config.format, Some(OutputFormat::Json)));
+        assert_eq!(config.allow_shallow, Some(true));
+        assert_eq!(config.min_age, Some(Duration::days(3)));
+        assert_eq!(config.max_age, Some(Duration::weeks(2)));
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = |text| Config::parse(text).err().unwrap();
+        assert_eq!(error("branch = \"main\"\nbranches = 1\n").0, 2);
+        let (line, message) = error("\n[thresholds]\ndays = \"soon\"\n");
+        assert_eq!(line, 3);
+        assert!(
+            message.starts_with("invalid duration \"soon\""),
+            "{message}"
+        );
+        let (line, message) = error("format = \"yaml\"\n");
+        assert_eq!(line, 1);
+        assert!(message.contains("expected one of text"), "{message}");
+        assert_eq!(error("branch = [1]").0, 1);
+    }
+}
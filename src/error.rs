@@ -0,0 +1,117 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::fmt;
+use std::io;
+use std::process::ExitStatus;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The `git` binary could not be started.
+    GitNotFound(io::Error),
+    /// A git command exited unsuccessfully.
+    GitFailed {
+        args: Vec<String>,
+        status: ExitStatus,
+        stderr: String,
+    },
+    /// A date in git output is not valid RFC 3339.
+    InvalidDate(String),
+    /// The repository is a shallow clone, so add commits of older changesets are missing.
+    ShallowHistory {
+        depth: usize,
+        oldest: DateTime<Utc>,
+        /// Start of the analysis window, `None` if the analysis covers all of history or only
+        /// pending changesets.
+        start: Option<DateTime<Utc>>,
+    },
+    /// A configuration file could not be read.
+    ConfigRead { path: String, error: io::Error },
+    /// The configuration file is malformed.
+    Config {
+        path: String,
+        line: usize,
+        message: String,
+    },
+    /// Reading the repository in process failed.
+    #[cfg(feature = "gix")]
+    Gitoxide(gix::Error),
+    /// Talking to a git process or writing output failed.
+    Io(io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::GitNotFound(e) => write!(f, "failed to run git: {e}"),
+            Error::GitFailed {
+                args,
+                status,
+                stderr,
+            } => {
+                write!(f, "git {args:?} failed with status {status}")?;
+                if !stderr.is_empty() {
+                    write!(f, ": {stderr}")?;
+                }
+                Ok(())
+            }
+            Error::InvalidDate(date) => write!(f, "failed to parse date {date:?} from git log"),
+            Error::ShallowHistory {
+                depth,
+                oldest,
+                start,
+            } => {
+                writeln!(
+                    f,
+                    "repository is a shallow clone ({depth} commits, oldest from {}); \
+                     changesets added before that would get wrong ages.",
+                    oldest.to_rfc3339_opts(SecondsFormat::Secs, true)
+                )?;
+                match start {
+                    Some(start) => write!(
+                        f,
+                        "Fetch more history, e.g. `git fetch --shallow-since={}` to cover the \
+                         window start or `git fetch --unshallow` for changesets opened earlier",
+                        start.to_rfc3339_opts(SecondsFormat::Secs, true)
+                    )?,
+                    None => write!(f, "Fetch the full history with `git fetch --unshallow`")?,
+                }
+                write!(f, ", or pass --allow-shallow to analyze anyway")
+            }
+            Error::ConfigRead { path, error } => write!(f, "failed to read {path}: {error}"),
+            Error::Config {
+                path,
+                line,
+                message,
+            } => write!(f, "{path}:{line}: {message}"),
+            #[cfg(feature = "gix")]
+            Error::Gitoxide(e) => write!(f, "{e}"),
+            Error::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::GitNotFound(e) | Error::Io(e) => Some(e),
+            Error::ConfigRead { error, .. } => Some(error),
+            #[cfg(feature = "gix")]
+            Error::Gitoxide(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+#[cfg(feature = "gix")]
+impl From<gix::Error> for Error {
+    fn from(e: gix::Error) -> Error {
+        Error::Gitoxide(e)
+    }
+}
@@ -0,0 +1,150 @@
+/// Semver bump level, ordered from least to most significant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum Bump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    pub fn label(self) -> &'static str {
+        match self {
+            Bump::None => "none",
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Bump> {
+        match s {
+            "none" => Some(Bump::None),
+            "patch" => Some(Bump::Patch),
+            "minor" => Some(Bump::Minor),
+            "major" => Some(Bump::Major),
+            _ => None,
+        }
+    }
+}
+
+/// One `"package": bump` entry from a changeset's frontmatter.
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub package: String,
+    pub bump: Bump,
+}
+
+/// Highest bump across all releases; a changeset without releases bumps nothing.
+pub fn highest_bump(releases: &[Release]) -> Bump {
+    releases.iter().map(|r| r.bump).max().unwrap_or(Bump::None)
+}
+
+fn unquote(s: &str) -> &str {
+    let s = s.trim();
+    for q in ['"', '\''] {
+        if let Some(inner) = s.strip_prefix(q).and_then(|s| s.strip_suffix(q)) {
+            return inner;
+        }
+    }
+    s
+}
+
+/// Split a `key: value` line, allowing the key to be quoted (scoped package names often are).
+fn split_entry(line: &str) -> Option<(&str, &str)> {
+    let idx = match line.chars().next()? {
+        q @ ('"' | '\'') => {
+            let close = line[1..].find(q)? + 1;
+            close + line[close..].find(':')?
+        }
+        _ => line.find(':')?,
+    };
+    let (key, value) = (unquote(&line[..idx]), unquote(&line[idx + 1..]));
+    (!key.is_empty() && !value.is_empty()).then_some((key, value))
+}
+
+/// Parse the YAML frontmatter of a changeset markdown file.
+///
+/// Returns `None` when the file has no `---` delimited frontmatter block. An empty block is
+/// valid and yields no releases.
+pub fn parse(content: &str) -> Option<Vec<Release>> {
+    let mut lines = content.trim_start_matches('\u{feff}').trim_start().lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+    let mut releases = Vec::new();
+    for line in lines {
+        let line = line.trim();
+        if line == "---" {
+            return Some(releases);
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (package, bump) = split_entry(line)?;
+        releases.push(Release {
+            package: package.to_string(),
+            bump: Bump::parse(bump)?,
+        });
+    }
+    // Unterminated frontmatter block
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn releases(content: &str) -> Option<Vec<(String, &'static str)>> {
+        parse(content).map(|releases| {
+            releases
+                .into_iter()
+                .map(|r| (r.package, r.bump.label()))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn quoted_scoped_package() {
+        assert_eq!(
+            releases("---\n\"@scope/pkg\": minor\n'other': patch\n---\n\nFix\n"),
+            Some(vec![
+                ("@scope/pkg".to_string(), "minor"),
+                ("other".to_string(), "patch")
+            ])
+        );
+    }
+
+    #[test]
+    fn split_entry_keeps_colons_in_quoted_keys() {
+        assert_eq!(split_entry("\"a:b\": major"), Some(("a:b", "major")));
+        assert_eq!(split_entry("pkg: 'patch'"), Some(("pkg", "patch")));
+        assert_eq!(split_entry("pkg:"), None);
+        assert_eq!(split_entry("\"unterminated: patch"), None);
+    }
+
+    #[test]
+    fn empty_block_has_no_releases() {
+        assert_eq!(releases("---\n---\n\nDocs only\n"), Some(Vec::new()));
+    }
+
+    #[test]
+    fn unterminated_block_is_not_a_changeset() {
+        assert_eq!(releases("---\npkg: patch\n"), None);
+        assert_eq!(releases("# README\n"), None);
+    }
+
+    #[test]
+    fn unknown_bump_is_not_a_changeset() {
+        assert_eq!(releases("---\npkg: huge\n---\n"), None);
+        assert_eq!(releases("---\npkg: Patch\n---\n"), None);
+    }
+
+    #[test]
+    fn bom_and_crlf() {
+        assert_eq!(
+            releases("\u{feff}---\r\npkg: major\r\n---\r\n\r\nFix\r\n"),
+            Some(vec![("pkg".to_string(), "major")])
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use crate::backend::{Backend, Shallow, Tag};
+use crate::error::{Error, Result};
+use crate::history::{Event, EventKind};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+fn spawn_error(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::NotFound => Error::GitNotFound(e),
+        _ => Error::Io(e),
+    }
+}
+
+fn git_stdout(dir: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(spawn_error)?;
+
+    if !output.status.success() {
+        return Err(Error::GitFailed {
+            args: args.iter().map(|a| a.to_string()).collect(),
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_date(ts: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| Error::InvalidDate(ts.to_string()))
+}
+
+/// [`Backend`] running the `git` binary in `dir`.
+pub struct GitCli {
+    dir: String,
+    blobs: Option<BlobReader>,
+}
+
+impl GitCli {
+    pub fn new(dir: &str) -> GitCli {
+        GitCli {
+            dir: dir.to_string(),
+            blobs: None,
+        }
+    }
+}
+
+impl Backend for GitCli {
+    fn history_events(
+        &mut self,
+        branch: &str,
+        dirs: &[String],
+        keep: &dyn Fn(&str) -> bool,
+    ) -> Result<BTreeMap<String, Vec<Event>>> {
+        history_events(&self.dir, branch, dirs, keep)
+    }
+
+    fn read_file(&mut self, commit: &str, path: &str) -> Result<Option<String>> {
+        let blobs = match &mut self.blobs {
+            Some(blobs) => blobs,
+            None => self.blobs.insert(BlobReader::new(&self.dir)?),
+        };
+        blobs.read(commit, path)
+    }
+
+    fn shallow(&mut self, branch: &str) -> Result<Option<Shallow>> {
+        let is_shallow = git_stdout(&self.dir, &["rev-parse", "--is-shallow-repository"])?;
+        if is_shallow.trim() != "true" {
+            return Ok(None);
+        }
+        let dates = git_stdout(&self.dir, &["log", branch, "--format=%cI"])?;
+        let mut depth = 0;
+        let mut oldest = None;
+        for line in dates.lines().filter(|l| !l.is_empty()) {
+            let date = parse_date(line.trim())?;
+            depth += 1;
+            oldest = Some(oldest.map_or(date, |o: DateTime<Utc>| o.min(date)));
+        }
+        Ok(oldest.map(|oldest| Shallow { depth, oldest }))
+    }
+
+    fn depth_of(&mut self, branch: &str, commit: &str) -> Result<usize> {
+        let range = format!("{commit}..{branch}");
+        let count = git_stdout(&self.dir, &["rev-list", "--count", &range])?;
+        Ok(count.trim().parse::<usize>().unwrap_or(0) + 1)
+    }
+
+    fn first_tag_containing(&mut self, commit: &str, pattern: Option<&str>) -> Result<Option<Tag>> {
+        let refs = format!("refs/tags/{}", pattern.unwrap_or(""));
+        let tags = git_stdout(
+            &self.dir,
+            &[
+                "for-each-ref",
+                "--contains",
+                commit,
+                "--sort=creatordate",
+                "--count=1",
+                "--format=%(creatordate:iso-strict) %(refname:short)",
+                &refs,
+            ],
+        )?;
+        let Some((ts, name)) = tags.trim().split_once(' ') else {
+            return Ok(None);
+        };
+        Ok(Some(Tag {
+            name: name.to_string(),
+            date: parse_date(ts)?,
+        }))
+    }
+}
+
+/// Separates commits in the `git log` output; it cannot appear in a path or a date.
+const COMMIT_MARKER: char = '\x01';
+
+/// Single `git log` pass over all `dirs`, see [`Backend::history_events`].
+fn history_events(
+    dir: &str,
+    branch: &str,
+    dirs: &[String],
+    keep: &dyn Fn(&str) -> bool,
+) -> Result<BTreeMap<String, Vec<Event>>> {
+    // Relative to the repository root wherever `dir` is; with glob magic a directory only
+    // matches the files beneath it through `/**`
+    let pathspecs: Vec<String> = dirs
+        .iter()
+        .map(|d| format!(":(top,glob){}/**", d.trim_end_matches('/')))
+        .collect();
+    let mut args = vec![
+        "log",
+        branch,
+        "--topo-order",
+        // No history simplification, like the gitoxide backend: a changeset added identically
+        // on both sides of a merge shows both adds
+        "--full-history",
+        "--diff-filter=AD",
+        "--no-renames",
+        "--name-status",
+        "-z",
+        "--format=%x01%H %aI",
+        "--",
+    ];
+    args.extend(pathspecs.iter().map(String::as_str));
+    let raw = git_stdout(dir, &args)?;
+
+    let mut events: BTreeMap<String, Vec<Event>> = BTreeMap::new();
+    for commit in raw.split(COMMIT_MARKER).filter(|c| !c.is_empty()) {
+        // "<hash> <date>\0\n<status>\0<path>\0<status>\0<path>\0..."
+        let mut fields = commit.split('\0');
+        let header = fields.next().unwrap_or_default().trim();
+        let (hash, ts) = header
+            .split_once(' ')
+            .ok_or_else(|| Error::InvalidDate(header.to_string()))?;
+        let date = parse_date(ts)?;
+        while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+            let kind = match status.trim() {
+                "A" => EventKind::Added,
+                "D" => EventKind::Deleted,
+                _ => continue,
+            };
+            if !keep(path) {
+                continue;
+            }
+            events.entry(path.to_string()).or_default().push(Event {
+                kind,
+                commit: hash.to_string(),
+                date,
+            });
+        }
+    }
+    // `git log` lists children before their parents
+    for path_events in events.values_mut() {
+        path_events.reverse();
+    }
+    Ok(events)
+}
+
+/// Reads file contents at given commits through one long-running `git cat-file --batch`.
+struct BlobReader {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl BlobReader {
+    fn new(dir: &str) -> Result<BlobReader> {
+        let mut child = Command::new("git")
+            .args(["cat-file", "--batch"])
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(spawn_error)?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+        Ok(BlobReader {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    /// Content of `path` as of `commit`, or `None` if it does not exist there.
+    fn read(&mut self, commit: &str, path: &str) -> Result<Option<String>> {
+        writeln!(self.stdin, "{commit}:{path}")?;
+        self.stdin.flush()?;
+
+        // "<oid> blob <size>\n<content>\n" or "<object> missing\n"
+        let mut header = String::new();
+        self.stdout.read_line(&mut header)?;
+        let size: usize = match header.trim_end().rsplit_once(' ') {
+            Some((kind, size)) if kind.ends_with(" blob") => match size.parse() {
+                Ok(size) => size,
+                Err(_) => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let mut content = vec![0; size + 1];
+        self.stdout.read_exact(&mut content)?;
+        content.truncate(size);
+        Ok(Some(String::from_utf8_lossy(&content).into_owned()))
+    }
+}
+
+impl Drop for BlobReader {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
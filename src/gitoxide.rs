@@ -0,0 +1,474 @@
+use crate::backend::{Backend, Shallow, Tag};
+use crate::error::{Error, Result};
+use crate::glob::Glob;
+use crate::history::{Event, EventKind};
+use chrono::{DateTime, Utc};
+use gix::ObjectId;
+use gix::bstr::ByteSlice;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+/// [`Backend`] reading the repository in process with gitoxide, so no `git` binary is needed.
+/// Built with the `gix` cargo feature.
+pub struct Gitoxide {
+    repo: gix::Repository,
+    /// First tag containing each commit, per tag pattern, see
+    /// [`Gitoxide::first_tag_containing`].
+    tags: Option<(Option<String>, HashMap<ObjectId, Tag>)>,
+}
+
+/// What a commit contributes to the history walk.
+struct CommitInfo {
+    id: ObjectId,
+    tree: ObjectId,
+    /// Empty for root commits and the boundary commits of a shallow clone.
+    parents: Vec<ObjectId>,
+    authored: DateTime<Utc>,
+    committed: DateTime<Utc>,
+}
+
+/// A tree entry. Only additions and deletions are reported, so file contents do not matter.
+enum Entry {
+    Tree(ObjectId),
+    File,
+}
+
+impl Gitoxide {
+    /// Open the repository containing `dir`.
+    pub fn open(dir: &str) -> Result<Gitoxide> {
+        Ok(Gitoxide {
+            repo: gix::discover(dir)?,
+            tags: None,
+        })
+    }
+
+    fn commit_id(&self, rev: &str) -> Result<ObjectId> {
+        let id = self.repo.rev_parse_single(rev)?;
+        Ok(id.object()?.peel_to_commit()?.id)
+    }
+
+    /// Commits whose parents are missing from a shallow clone.
+    fn shallow_boundary(&self) -> Result<HashSet<ObjectId>> {
+        Ok(match self.repo.shallow_commits()? {
+            Some(commits) => commits.iter().copied().collect(),
+            None => HashSet::new(),
+        })
+    }
+
+    fn commit_info(&self, id: ObjectId, boundary: &HashSet<ObjectId>) -> Result<CommitInfo> {
+        let commit = self.repo.find_commit(id)?;
+        let parents = match boundary.contains(&id) {
+            true => Vec::new(),
+            false => commit.parent_ids().map(|p| p.detach()).collect(),
+        };
+        Ok(CommitInfo {
+            id,
+            tree: commit.tree_id()?.detach(),
+            parents,
+            authored: timestamp(commit.author()?.time()?.seconds)?,
+            committed: timestamp(commit.time()?.seconds)?,
+        })
+    }
+
+    /// Every commit reachable from `tip`, each after all of its parents, like
+    /// `git log --topo-order --reverse`.
+    fn walk(&self, tip: ObjectId) -> Result<Vec<CommitInfo>> {
+        let boundary = self.shallow_boundary()?;
+        enum Step {
+            Visit(ObjectId),
+            Emit(CommitInfo),
+        }
+        let mut seen = HashSet::new();
+        let mut stack = vec![Step::Visit(tip)];
+        let mut commits = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Emit(commit) => commits.push(commit),
+                Step::Visit(id) => {
+                    if !seen.insert(id) {
+                        continue;
+                    }
+                    let commit = self.commit_info(id, &boundary)?;
+                    let parents: Vec<Step> = commit
+                        .parents
+                        .iter()
+                        .rev()
+                        .filter(|p| !seen.contains(*p))
+                        .map(|p| Step::Visit(*p))
+                        .collect();
+                    stack.push(Step::Emit(commit));
+                    stack.extend(parents);
+                }
+            }
+        }
+        Ok(commits)
+    }
+
+    fn entries(&self, tree: Option<ObjectId>) -> Result<BTreeMap<String, Entry>> {
+        let Some(tree) = tree else {
+            return Ok(BTreeMap::new());
+        };
+        let tree = self.repo.find_tree(tree)?;
+        let entries = tree
+            .decode()?
+            .entries
+            .iter()
+            .filter_map(|e| {
+                let entry = match e.mode {
+                    mode if mode.is_tree() => Entry::Tree(e.oid.to_owned()),
+                    mode if mode.is_blob_or_symlink() => Entry::File,
+                    // Submodules
+                    _ => return None,
+                };
+                Some((e.filename.to_str_lossy().into_owned(), entry))
+            })
+            .collect();
+        Ok(entries)
+    }
+
+    /// Files added and deleted between two trees, like `git diff --name-status`, only
+    /// descending into subtrees that changed and may hold a changeset directory.
+    fn diff_trees(
+        &self,
+        old: Option<ObjectId>,
+        new: Option<ObjectId>,
+        prefix: &str,
+        scope: &Scope,
+        changes: &mut Vec<(EventKind, String)>,
+    ) -> Result<()> {
+        let old = self.entries(old)?;
+        let new = self.entries(new)?;
+        let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        for name in names {
+            let path = match prefix.is_empty() {
+                true => name.clone(),
+                false => format!("{prefix}/{name}"),
+            };
+            let (old, new) = (old.get(name), new.get(name));
+            let tree = |e: Option<&Entry>| match e {
+                Some(Entry::Tree(id)) => Some(*id),
+                _ => None,
+            };
+            let (old_tree, new_tree) = (tree(old), tree(new));
+            if old_tree != new_tree && scope.may_contain(&path) {
+                self.diff_trees(old_tree, new_tree, &path, scope, changes)?;
+            }
+            let file = |e: Option<&Entry>| matches!(e, Some(Entry::File));
+            let kind = match (file(old), file(new)) {
+                (true, false) => EventKind::Deleted,
+                (false, true) => EventKind::Added,
+                _ => continue,
+            };
+            if scope.contains(&path) {
+                changes.push((kind, path));
+            }
+        }
+        Ok(())
+    }
+
+    /// Map every commit reachable from a tag matching `pattern` to the earliest created such
+    /// tag, in one walk over the history.
+    fn index_tags(&self, pattern: Option<&str>) -> Result<HashMap<ObjectId, Tag>> {
+        let mut tags = Vec::new();
+        for reference in self.repo.references()?.tags()? {
+            let reference = reference?;
+            let name = reference.name().shorten().to_str_lossy().into_owned();
+            if pattern.is_some_and(|p| !tag_matches(p, &name)) {
+                continue;
+            }
+            let object = reference.id().object()?;
+            let tagged = match object.kind {
+                gix::object::Kind::Tag => object.into_tag().tagger()?.map(|t| t.seconds()),
+                _ => None,
+            };
+            // Tags of trees or blobs contain no commits
+            let Ok(id) = reference.id().object()?.peel_to_commit() else {
+                continue;
+            };
+            let date = match tagged {
+                Some(seconds) => timestamp(seconds)?,
+                None => timestamp(id.time()?.seconds)?,
+            };
+            tags.push((date, name, id.id));
+        }
+        tags.sort();
+
+        // Commits reachable from an earlier tag are skipped along with their ancestors, which
+        // that tag reaches too
+        let boundary = self.shallow_boundary()?;
+        let mut first = HashMap::new();
+        for (date, name, tip) in tags {
+            let tag = Tag { name, date };
+            let mut stack = vec![tip];
+            while let Some(id) = stack.pop() {
+                if first.contains_key(&id) {
+                    continue;
+                }
+                first.insert(id, tag.clone());
+                if boundary.contains(&id) {
+                    continue;
+                }
+                let commit = self.repo.find_commit(id)?;
+                stack.extend(commit.parent_ids().map(|p| p.detach()));
+            }
+        }
+        Ok(first)
+    }
+}
+
+impl Backend for Gitoxide {
+    fn history_events(
+        &mut self,
+        branch: &str,
+        dirs: &[String],
+        keep: &dyn Fn(&str) -> bool,
+    ) -> Result<BTreeMap<String, Vec<Event>>> {
+        let scope = Scope::new(dirs);
+        let commits = self.walk(self.commit_id(branch)?)?;
+        let trees: HashMap<ObjectId, ObjectId> = commits.iter().map(|c| (c.id, c.tree)).collect();
+
+        let mut events: BTreeMap<String, Vec<Event>> = BTreeMap::new();
+        let mut changes = Vec::new();
+        for commit in &commits {
+            // Like `git log`, merges show no changes of their own
+            let parent_tree = match commit.parents.as_slice() {
+                [] => None,
+                [parent] => Some(trees[parent]),
+                _ => continue,
+            };
+            self.diff_trees(parent_tree, Some(commit.tree), "", &scope, &mut changes)?;
+            for (kind, path) in changes.drain(..) {
+                if !keep(&path) {
+                    continue;
+                }
+                events.entry(path).or_default().push(Event {
+                    kind,
+                    commit: commit.id.to_string(),
+                    date: commit.authored,
+                });
+            }
+        }
+        Ok(events)
+    }
+
+    fn read_file(&mut self, commit: &str, path: &str) -> Result<Option<String>> {
+        // Like `git cat-file`, a revision that does not exist has no files, e.g. the parent of
+        // a root commit
+        let Ok(id) = self.commit_id(commit) else {
+            return Ok(None);
+        };
+        let tree = self.repo.find_commit(id)?.tree()?;
+        let Some(entry) = tree.lookup_entry_by_path(path)? else {
+            return Ok(None);
+        };
+        if !entry.mode().is_blob() {
+            return Ok(None);
+        }
+        let blob = entry.object()?;
+        Ok(Some(String::from_utf8_lossy(&blob.data).into_owned()))
+    }
+
+    fn shallow(&mut self, branch: &str) -> Result<Option<Shallow>> {
+        if !self.repo.is_shallow()? {
+            return Ok(None);
+        }
+        let commits = self.walk(self.commit_id(branch)?)?;
+        let oldest = commits.iter().map(|c| c.committed).min();
+        Ok(oldest.map(|oldest| Shallow {
+            depth: commits.len(),
+            oldest,
+        }))
+    }
+
+    fn depth_of(&mut self, branch: &str, commit: &str) -> Result<usize> {
+        let reachable: HashSet<ObjectId> = self
+            .walk(self.commit_id(commit)?)?
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        let newer = self
+            .walk(self.commit_id(branch)?)?
+            .iter()
+            .filter(|c| !reachable.contains(&c.id))
+            .count();
+        Ok(newer + 1)
+    }
+
+    fn first_tag_containing(&mut self, commit: &str, pattern: Option<&str>) -> Result<Option<Tag>> {
+        let id = self.commit_id(commit)?;
+        let indexed = self
+            .tags
+            .as_ref()
+            .is_some_and(|(p, _)| p.as_deref() == pattern);
+        if !indexed {
+            let first = self.index_tags(pattern)?;
+            self.tags = Some((pattern.map(str::to_string), first));
+        }
+        let (_, first) = self.tags.as_ref().expect("tags indexed");
+        Ok(first.get(&id).cloned())
+    }
+}
+
+/// The changeset directories of the analysis, to only descend into trees that may hold them.
+struct Scope {
+    dirs: Vec<Glob>,
+    /// Leading segments of each directory up to the first one with a wildcard.
+    literal: Vec<Vec<String>>,
+}
+
+impl Scope {
+    fn new(dirs: &[String]) -> Scope {
+        let dirs: Vec<&str> = dirs.iter().map(|d| d.trim_end_matches('/')).collect();
+        Scope {
+            dirs: dirs.iter().map(|d| Glob::full(d)).collect(),
+            literal: dirs
+                .iter()
+                .map(|d| {
+                    d.split('/')
+                        .take_while(|s| !s.contains(['*', '?', '[']))
+                        .map(str::to_string)
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether the directory `path` is inside, or leads to, a changeset directory.
+    fn may_contain(&self, path: &str) -> bool {
+        let segments: Vec<&str> = path.split('/').collect();
+        self.literal
+            .iter()
+            .any(|literal| segments.iter().zip(literal).all(|(s, l)| s == l))
+    }
+
+    /// Whether the file `path` is inside a changeset directory.
+    fn contains(&self, path: &str) -> bool {
+        Path::new(path)
+            .ancestors()
+            .skip(1)
+            .any(|dir| self.dirs.iter().any(|d| d.matches(&dir.to_string_lossy())))
+    }
+}
+
+/// `git for-each-ref` matching of a short tag name: a glob, or a literal prefix followed by
+/// a `/`.
+fn tag_matches(pattern: &str, name: &str) -> bool {
+    let prefix = pattern.trim_end_matches('/');
+    Glob::full(pattern).matches(name)
+        || name
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn timestamp(seconds: i64) -> Result<DateTime<Utc>> {
+    DateTime::from_timestamp(seconds, 0).ok_or_else(|| Error::InvalidDate(seconds.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::GitCli;
+    use crate::history;
+    use std::fs;
+    use std::process::Command;
+
+    /// Runs `git` in `dir` as of January `day`, 2025, ignoring the user's configuration.
+    fn git(dir: &Path, day: u32, args: &[&str]) -> String {
+        let date = format!("2025-01-{day:02}T00:00:00Z");
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "git {args:?}: {stderr}");
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    fn commit_file(dir: &Path, day: u32, path: &str, message: &str) -> String {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "---\n\"pkg\": patch\n---\n\nFix\n").unwrap();
+        git(dir, day, &["add", "."]);
+        git(dir, day, &["commit", "-q", "-m", message]);
+        git(dir, day, &["rev-parse", "HEAD"])
+    }
+
+    /// The add commit of every interval of `path`.
+    fn adds(backend: &mut dyn Backend, path: &str) -> Vec<Option<String>> {
+        let mut events = backend
+            .history_events("master", &[".changeset".to_string()], &|_| true)
+            .unwrap();
+        history::intervals(events.remove(path).unwrap())
+            .into_iter()
+            .map(|i| i.added.map(|(commit, _)| commit))
+            .collect()
+    }
+
+    #[test]
+    fn backends_agree_on_a_changeset_added_on_both_sides_of_a_merge() {
+        let dir = std::env::temp_dir().join(format!("changeset-lifetime-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        git(&dir, 1, &["init", "-q", "-b", "master"]);
+        commit_file(&dir, 1, "README.md", "init");
+        git(&dir, 2, &["checkout", "-q", "-b", "side"]);
+        let side_add = commit_file(&dir, 2, ".changeset/fox.md", "add on side");
+        git(&dir, 3, &["checkout", "-q", "master"]);
+        commit_file(&dir, 3, ".changeset/fox.md", "add on master");
+        git(&dir, 4, &["merge", "-q", "--no-edit", "side"]);
+
+        let path = ".changeset/fox.md";
+        let git_adds = adds(&mut GitCli::new(dir.to_str().unwrap()), path);
+        let gitoxide_adds = adds(&mut Gitoxide::open(dir.to_str().unwrap()).unwrap(), path);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(git_adds, [Some(side_add)]);
+        assert_eq!(gitoxide_adds, git_adds);
+    }
+
+    #[test]
+    fn scope_descends_only_towards_changeset_directories() {
+        let scope = Scope::new(&[
+            ".changeset".to_string(),
+            "packages/*/.changeset/".to_string(),
+        ]);
+        assert!(scope.may_contain(".changeset"));
+        assert!(scope.may_contain("packages"));
+        assert!(scope.may_contain("packages/a"));
+        assert!(scope.may_contain("packages/a/.changeset/drafts"));
+        assert!(!scope.may_contain("src"));
+        assert!(!scope.may_contain("docs/.changeset"));
+    }
+
+    #[test]
+    fn scope_contains_files_below_matching_directories() {
+        let scope = Scope::new(&[
+            ".changeset".to_string(),
+            "packages/*/.changeset".to_string(),
+        ]);
+        assert!(scope.contains(".changeset/red-fox.md"));
+        assert!(scope.contains(".changeset/drafts/red-fox.md"));
+        assert!(scope.contains("packages/a/.changeset/red-fox.md"));
+        assert!(!scope.contains("packages/a/b/.changeset/red-fox.md"));
+        assert!(!scope.contains("packages/a/README.md"));
+        assert!(!scope.contains(".changeset"));
+    }
+
+    #[test]
+    fn tag_patterns_match_like_for_each_ref() {
+        assert!(tag_matches("v*", "v1.0.0"));
+        assert!(!tag_matches("v*", "@scope/pkg@1.0.0"));
+        assert!(tag_matches("@scope/pkg@*", "@scope/pkg@1.0.0"));
+        assert!(tag_matches("release", "release/1.0"));
+        assert!(tag_matches("release/", "release/1.0"));
+        assert!(!tag_matches("release", "releases/1.0"));
+    }
+}
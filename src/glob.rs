@@ -0,0 +1,178 @@
+/// Shell style glob pattern.
+///
+/// Supports `?`, `*` (within a path segment), `**` (across segments) and `[...]` classes
+/// with ranges and `!`/`^` negation. Patterns without a `/` match the file name only.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    pattern: Vec<char>,
+    name_only: bool,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Glob {
+        Glob {
+            pattern: pattern.chars().collect(),
+            name_only: !pattern.contains('/'),
+        }
+    }
+
+    /// Matches the whole text even without a `/` in the pattern, e.g. for package names.
+    pub fn full(pattern: &str) -> Glob {
+        Glob {
+            pattern: pattern.chars().collect(),
+            name_only: false,
+        }
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let text = match self.name_only {
+            true => path.rsplit('/').next().unwrap_or(path),
+            false => path,
+        };
+        let text: Vec<char> = text.chars().collect();
+        match_from(&self.pattern, &text)
+    }
+}
+
+impl std::str::FromStr for Glob {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Glob, Self::Err> {
+        Ok(Glob::new(s))
+    }
+}
+
+/// Match a `[...]` class starting just after the `[`. Returns whether `c` matched and the
+/// pattern index after the closing `]`, or `None` if the class is unterminated.
+fn match_class(pattern: &[char], c: char) -> Option<(bool, usize)> {
+    let mut i = 0;
+    let negated = matches!(pattern.first(), Some('!' | '^'));
+    if negated {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    while i < pattern.len() {
+        if pattern[i] == ']' && !first {
+            return Some((matched != negated, i + 1));
+        }
+        first = false;
+        if i + 2 < pattern.len() && pattern[i + 1] == '-' && pattern[i + 2] != ']' {
+            matched |= pattern[i] <= c && c <= pattern[i + 2];
+            i += 3;
+        } else {
+            matched |= pattern[i] == c;
+            i += 1;
+        }
+    }
+    None
+}
+
+fn match_from(pattern: &[char], text: &[char]) -> bool {
+    let Some(&p) = pattern.first() else {
+        return text.is_empty();
+    };
+    match p {
+        '*' if pattern.get(1) == Some(&'*') => {
+            // `**/` also matches zero directories
+            let rest = &pattern[2..];
+            if let Some(after_slash) = rest.strip_prefix(&['/'])
+                && match_from(after_slash, text)
+            {
+                return true;
+            }
+            (0..=text.len()).any(|i| match_from(rest, &text[i..]))
+        }
+        '*' => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if match_from(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        '?' => !text.is_empty() && text[0] != '/' && match_from(&pattern[1..], &text[1..]),
+        '[' => {
+            let Some(&c) = text.first() else {
+                return false;
+            };
+            match match_class(&pattern[1..], c) {
+                Some((matched, end)) => {
+                    matched && c != '/' && match_from(&pattern[1 + end..], &text[1..])
+                }
+                // Unterminated class: treat `[` literally
+                None => c == '[' && match_from(&pattern[1..], &text[1..]),
+            }
+        }
+        c => text.first() == Some(&c) && match_from(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_stays_within_a_segment() {
+        let glob = Glob::new(".changeset/*.md");
+        assert!(glob.matches(".changeset/red-fox.md"));
+        assert!(!glob.matches(".changeset/drafts/red-fox.md"));
+        assert!(Glob::new("a*b/c").matches("ab/c"));
+    }
+
+    #[test]
+    fn double_star_crosses_segments() {
+        let glob = Glob::new("packages/**/*.md");
+        assert!(glob.matches("packages/a.md"));
+        assert!(glob.matches("packages/a/.changeset/b.md"));
+        assert!(!glob.matches("other/a.md"));
+        assert!(Glob::new("**").matches("a/b/c"));
+        assert!(Glob::new("a/**b").matches("a/x/yb"));
+    }
+
+    #[test]
+    fn question_mark_matches_one_character_but_not_a_slash() {
+        assert!(Glob::new("a?c/d").matches("abc/d"));
+        assert!(!Glob::new("a?c/d").matches("a/c/d"));
+        assert!(!Glob::new("a?c/d").matches("ac/d"));
+    }
+
+    #[test]
+    fn classes() {
+        let glob = Glob::new("[a-c]x.md");
+        assert!(glob.matches("bx.md"));
+        assert!(!glob.matches("dx.md"));
+        assert!(Glob::new("[!a-c]x").matches("dx"));
+        assert!(Glob::new("[^a-c]x").matches("dx"));
+        assert!(!Glob::new("[!a-c]x").matches("ax"));
+        // `]` first in the class is literal, a trailing `-` too
+        assert!(Glob::new("[]]").matches("]"));
+        assert!(Glob::new("[a-]").matches("-"));
+        assert!(!Glob::new("x[/]y/z").matches("x/y/z"));
+    }
+
+    #[test]
+    fn unterminated_class_is_literal() {
+        assert!(Glob::new("[ab").matches("[ab"));
+        assert!(!Glob::new("[ab").matches("a"));
+    }
+
+    #[test]
+    fn patterns_without_slash_match_the_file_name() {
+        assert!(Glob::new("*.md").matches(".changeset/drafts/a.md"));
+        assert!(!Glob::new("*.md").matches(".changeset/a.txt"));
+        assert!(!Glob::full("*.md").matches(".changeset/a.md"));
+    }
+
+    #[test]
+    fn full_matches_package_names() {
+        let glob = Glob::full("@scope/*");
+        assert!(glob.matches("@scope/a"));
+        assert!(!glob.matches("@other/a"));
+        assert!(!Glob::full("pkg").matches("@scope/pkg"));
+    }
+}
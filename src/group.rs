@@ -0,0 +1,113 @@
+use crate::ChangesetLifetime;
+use crate::glob::Glob;
+use crate::stats::{Stat, Summary};
+use chrono::Duration;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum GroupBy {
+    /// Every package named in the changeset frontmatter
+    Package,
+    /// Highest semver bump in the changeset
+    Bump,
+    /// Fixed or linked package group from the Changesets config; other packages on their own
+    PackageGroup,
+    /// Changeset directory, e.g. one per workspace with `packages/*/.changeset`
+    Directory,
+}
+
+impl GroupBy {
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupBy::Package => "package",
+            GroupBy::Bump => "bump",
+            GroupBy::PackageGroup => "package_group",
+            GroupBy::Directory => "directory",
+        }
+    }
+
+    /// Groups a changeset contributes to. A changeset may belong to several groups, or none.
+    fn keys(self, cs: &ChangesetLifetime, package_groups: &[PackageGroup]) -> Vec<String> {
+        match self {
+            GroupBy::Package => cs.releases.iter().map(|r| r.package.clone()).collect(),
+            GroupBy::Bump => vec![cs.bump().label().to_string()],
+            GroupBy::PackageGroup => {
+                let mut keys: Vec<String> = cs
+                    .releases
+                    .iter()
+                    .map(|r| {
+                        package_groups
+                            .iter()
+                            .find(|g| g.members.iter().any(|m| m.matches(&r.package)))
+                            .map_or_else(|| r.package.clone(), |g| g.name.clone())
+                    })
+                    .collect();
+                // Packages of one group are released together, so count the changeset once
+                keys.sort();
+                keys.dedup();
+                keys
+            }
+            GroupBy::Directory => vec![cs.directory.clone()],
+        }
+    }
+}
+
+/// Packages that are versioned together, from the `fixed` or `linked` setting of the
+/// Changesets config.
+#[derive(Clone)]
+pub struct PackageGroup {
+    pub name: String,
+    /// Package names or globs such as `@scope/*`.
+    pub members: Vec<Glob>,
+}
+
+pub struct Group {
+    pub key: String,
+    pub summary: Summary,
+}
+
+pub struct Grouping {
+    pub by: GroupBy,
+    pub groups: Vec<Group>,
+}
+
+/// Collects ages per group key while changesets are resolved.
+pub struct Grouper {
+    group_by: GroupBy,
+    package_groups: Vec<PackageGroup>,
+    ages: BTreeMap<String, Vec<Duration>>,
+}
+
+impl Grouper {
+    /// `package_groups` is only used with [`GroupBy::PackageGroup`].
+    pub fn new(group_by: GroupBy, package_groups: Vec<PackageGroup>) -> Grouper {
+        Grouper {
+            group_by,
+            package_groups,
+            ages: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, cs: &ChangesetLifetime) {
+        for key in self.group_by.keys(cs, &self.package_groups) {
+            self.ages.entry(key).or_default().push(cs.age);
+        }
+    }
+
+    /// Every group reports its median in addition to the requested statistics.
+    pub fn finish(self, stats: &[Stat]) -> Grouping {
+        let stats = Stat::with_median(stats);
+        let groups = self
+            .ages
+            .into_iter()
+            .map(|(key, ages)| Group {
+                key,
+                summary: Summary::from_ages(&ages, &stats),
+            })
+            .collect();
+        Grouping {
+            by: self.group_by,
+            groups,
+        }
+    }
+}
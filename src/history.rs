@@ -0,0 +1,169 @@
+use chrono::{DateTime, Utc};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Added,
+    Deleted,
+}
+
+/// A commit that added or deleted a changeset path.
+pub struct Event {
+    pub kind: EventKind,
+    pub commit: String,
+    pub date: DateTime<Utc>,
+}
+
+/// One add/delete cycle of a path. `removed` is `None` while the changeset is still pending;
+/// `added` is `None` if the delete could not be paired with an add.
+pub struct Interval {
+    pub added: Option<(String, DateTime<Utc>)>,
+    pub removed: Option<(String, DateTime<Utc>)>,
+}
+
+/// Pair adds with the next delete, so a path that was released and later re-added yields one
+/// interval per cycle.
+///
+/// `events` must be in history order, oldest first, as returned by
+/// [`crate::backend::Backend::history_events`]. Dates only measure ages: a re-add rebased from
+/// an older branch keeps its earlier author date but still comes after the delete it follows.
+///
+/// Adds on two merged branches without a delete in between keep the older add, and so do
+/// repeated deletes, so it does not matter which branch a backend lists first. A delete
+/// without any preceding add, e.g. because the history is truncated, yields an interval
+/// without `added`.
+pub fn intervals(events: Vec<Event>) -> Vec<Interval> {
+    let mut intervals: Vec<Interval> = Vec::new();
+    let mut open: Option<(String, DateTime<Utc>)> = None;
+    let mut previous = None;
+    for event in events {
+        let older = |kept: &Option<(String, DateTime<Utc>)>| {
+            kept.as_ref().is_none_or(|(_, date)| event.date < *date)
+        };
+        match (event.kind, previous, intervals.last_mut()) {
+            (EventKind::Added, _, _) => {
+                if older(&open) {
+                    open = Some((event.commit, event.date));
+                }
+            }
+            (EventKind::Deleted, Some(EventKind::Deleted), Some(last)) => {
+                if older(&last.removed) {
+                    last.removed = Some((event.commit, event.date));
+                }
+            }
+            (EventKind::Deleted, _, _) => intervals.push(Interval {
+                added: open.take(),
+                removed: Some((event.commit, event.date)),
+            }),
+        }
+        previous = Some(event.kind);
+    }
+    if let Some(added) = open {
+        intervals.push(Interval {
+            added: Some(added),
+            removed: None,
+        });
+    }
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone};
+
+    fn event(kind: EventKind, commit: &str, day: u32) -> Event {
+        Event {
+            kind,
+            commit: commit.to_string(),
+            date: Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).unwrap(),
+        }
+    }
+
+    fn commits(intervals: &[Interval]) -> Vec<(Option<&str>, Option<&str>)> {
+        intervals
+            .iter()
+            .map(|i| {
+                (
+                    i.added.as_ref().map(|(c, _)| c.as_str()),
+                    i.removed.as_ref().map(|(c, _)| c.as_str()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn re_add_starts_a_new_cycle() {
+        let events = vec![
+            event(EventKind::Added, "a1", 1),
+            event(EventKind::Deleted, "d1", 5),
+            event(EventKind::Added, "a2", 10),
+        ];
+        assert_eq!(
+            commits(&intervals(events)),
+            [(Some("a1"), Some("d1")), (Some("a2"), None)]
+        );
+    }
+
+    #[test]
+    fn re_add_with_older_author_date_follows_history_order() {
+        // Rebased onto the release, but authored before it
+        let events = vec![
+            event(EventKind::Added, "a1", 5),
+            event(EventKind::Deleted, "d1", 20),
+            event(EventKind::Added, "a2", 10),
+        ];
+        let intervals = intervals(events);
+        assert_eq!(
+            commits(&intervals),
+            [(Some("a1"), Some("d1")), (Some("a2"), None)]
+        );
+        assert_eq!(intervals[1].added.as_ref().unwrap().1.day(), 10);
+    }
+
+    #[test]
+    fn unpaired_delete_has_no_add() {
+        let events = vec![
+            event(EventKind::Deleted, "d1", 5),
+            event(EventKind::Added, "a1", 10),
+            event(EventKind::Deleted, "d2", 15),
+        ];
+        assert_eq!(
+            commits(&intervals(events)),
+            [(None, Some("d1")), (Some("a1"), Some("d2"))]
+        );
+    }
+
+    #[test]
+    fn repeated_add_keeps_the_first() {
+        let events = vec![
+            event(EventKind::Added, "a1", 1),
+            event(EventKind::Added, "a2", 3),
+            event(EventKind::Deleted, "d1", 5),
+        ];
+        assert_eq!(commits(&intervals(events)), [(Some("a1"), Some("d1"))]);
+    }
+
+    #[test]
+    fn adds_and_deletes_on_merged_branches_keep_the_older() {
+        // Added on a branch and on master, then released on both
+        let events = vec![
+            event(EventKind::Added, "master-add", 3),
+            event(EventKind::Added, "branch-add", 1),
+            event(EventKind::Deleted, "master-release", 9),
+            event(EventKind::Deleted, "branch-release", 7),
+            event(EventKind::Added, "re-add", 12),
+        ];
+        assert_eq!(
+            commits(&intervals(events)),
+            [
+                (Some("branch-add"), Some("branch-release")),
+                (Some("re-add"), None)
+            ]
+        );
+    }
+
+    #[test]
+    fn no_events() {
+        assert!(intervals(Vec::new()).is_empty());
+    }
+}
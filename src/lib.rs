@@ -0,0 +1,363 @@
+//! Measure how long changesets stay in a repository before a release removes them.
+//!
+//! [`lifetimes`] runs the analysis against a local checkout using the `git` binary;
+//! [`analyze`] and [`analyze_with`] accept any [`Backend`], such as `gitoxide::Gitoxide`, which
+//! reads the repository in process when built with the `gix` feature.
+
+pub mod backend;
+pub mod classify;
+pub mod compare;
+pub mod config;
+pub mod error;
+pub mod frontmatter;
+pub mod git;
+#[cfg(feature = "gix")]
+pub mod gitoxide;
+pub mod glob;
+pub mod group;
+pub mod history;
+pub mod output;
+pub mod queue;
+pub mod stats;
+pub mod survival;
+pub mod timespec;
+pub mod tool_config;
+pub mod trend;
+
+use backend::{Backend, Tag};
+use chrono::{DateTime, Duration, Utc};
+use classify::Classifier;
+use frontmatter::{Bump, Release};
+use glob::Glob;
+use history::Interval;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub use error::{Error, Result};
+
+/// One add/delete cycle of a changeset file.
+#[derive(Debug, Clone)]
+pub struct ChangesetLifetime {
+    pub name: String,
+    pub path: String,
+    /// Changeset directory the file was found in, e.g. `packages/a/.changeset`.
+    pub directory: String,
+    pub commit_added: String,
+    pub commit_removed: Option<String>,
+    pub created: DateTime<Utc>,
+    pub removed: Option<DateTime<Utc>>,
+    /// Time until removal, or until now (or `end` with [`Options::as_of_end`]) for pending
+    /// changesets, truncated to minutes.
+    pub age: Duration,
+    pub releases: Vec<Release>,
+    /// First release tag containing the removal commit, if tags were resolved.
+    pub tag: Option<Tag>,
+}
+
+impl ChangesetLifetime {
+    pub fn bump(&self) -> Bump {
+        frontmatter::highest_bump(&self.releases)
+    }
+
+    /// Time from adding the changeset until its release was tagged, truncated to minutes.
+    /// Zero if the tag is dated before the add commit, e.g. after clock skew or a rebase.
+    pub fn time_to_tag(&self) -> Option<Duration> {
+        let tag = self.tag.as_ref()?;
+        let minutes = (tag.date - self.created).num_minutes().max(0);
+        Some(Duration::minutes(minutes))
+    }
+}
+
+/// A changeset deletion whose add commit is not in the analyzed history, e.g. because the
+/// history is truncated or the file was added on a branch that was never merged. Its age is
+/// unknown, so it is not part of any statistics.
+#[derive(Debug, Clone)]
+pub struct UnknownOrigin {
+    pub name: String,
+    pub path: String,
+    pub directory: String,
+    pub commit_removed: String,
+    pub removed: DateTime<Utc>,
+    /// Read from the content just before removal.
+    pub releases: Vec<Release>,
+}
+
+impl UnknownOrigin {
+    pub fn bump(&self) -> Bump {
+        frontmatter::highest_bump(&self.releases)
+    }
+}
+
+/// One result of the analysis.
+#[derive(Debug, Clone)]
+pub enum Record {
+    Lifetime(ChangesetLifetime),
+    UnknownOrigin(UnknownOrigin),
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    /// Longest-lived first.
+    pub changesets: Vec<ChangesetLifetime>,
+    pub unknown_origin: Vec<UnknownOrigin>,
+}
+
+/// Which changesets to report.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub branch: String,
+    /// Directories holding the changesets, relative to the repository root. Globs such as
+    /// `packages/*/.changeset` match one directory per workspace.
+    pub changeset_dirs: Vec<String>,
+    /// Changesets removed before `start` or added at or after `end` are skipped: the window
+    /// includes `start` and excludes `end`.
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Changesets younger than this are skipped.
+    pub min_age: Duration,
+    pub include: Vec<Glob>,
+    pub exclude: Vec<Glob>,
+    /// Only keep changesets whose highest bump is listed; empty keeps all.
+    pub bumps: Vec<Bump>,
+    /// Changesets that only release packages matching these are skipped.
+    pub ignore_packages: Vec<Glob>,
+    /// Analyze shallow clones instead of failing with [`Error::ShallowHistory`].
+    pub allow_shallow: bool,
+    /// Resolve the release tag of every removal commit.
+    pub release_tags: bool,
+    /// Only consider tags matching this glob, e.g. `@scope/pkg@*`.
+    pub tag_pattern: Option<String>,
+    /// Reconstruct the repository state at `end`: adds and deletes after it are ignored and
+    /// pending changesets are aged until `end` instead of now, so reports over past windows
+    /// are reproducible.
+    pub as_of_end: bool,
+}
+
+impl Options {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Options {
+        Options {
+            branch: "master".to_string(),
+            changeset_dirs: vec![".changeset".to_string()],
+            start,
+            end,
+            min_age: Duration::zero(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            bumps: Vec::new(),
+            ignore_packages: Vec::new(),
+            allow_shallow: false,
+            release_tags: false,
+            tag_pattern: None,
+            as_of_end: false,
+        }
+    }
+}
+
+/// Packages released by the changeset, read from its content at the add commit.
+/// `None` if the file has no valid frontmatter and so is not a changeset.
+fn changeset_releases(
+    backend: &mut dyn Backend,
+    commit: &str,
+    path: &str,
+) -> Result<Option<Vec<Release>>> {
+    let content = backend.read_file(commit, path)?;
+    Ok(content.as_deref().and_then(frontmatter::parse))
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// The configured changeset directory containing `path`: its closest ancestor matching one
+/// of [`Options::changeset_dirs`], or its parent if none does.
+fn directory(options: &Options, path: &str) -> String {
+    let dirs: Vec<Glob> = options
+        .changeset_dirs
+        .iter()
+        .map(|d| Glob::full(d.trim_end_matches('/')))
+        .collect();
+    let parent = Path::new(path).parent().unwrap_or(Path::new(""));
+    parent
+        .ancestors()
+        .map(|a| a.to_string_lossy())
+        .find(|a| dirs.iter().any(|d| d.matches(a)))
+        .unwrap_or_else(|| parent.to_string_lossy())
+        .to_string()
+}
+
+/// Whether the bump or ignored package filters drop a changeset with these releases.
+fn filtered(options: &Options, releases: &[Release]) -> bool {
+    let bump_filtered =
+        !options.bumps.is_empty() && !options.bumps.contains(&frontmatter::highest_bump(releases));
+    let only_ignored = !releases.is_empty()
+        && releases.iter().all(|r| {
+            options
+                .ignore_packages
+                .iter()
+                .any(|g| g.matches(&r.package))
+        });
+    bump_filtered || only_ignored
+}
+
+/// A delete that could not be paired with an add. The content is read from the parent of the
+/// removal commit since the add commit is unknown.
+fn unknown_origin(
+    options: &Options,
+    backend: &mut dyn Backend,
+    path: &str,
+    (commit_removed, removed): (String, DateTime<Utc>),
+) -> Result<Option<UnknownOrigin>> {
+    if removed < options.start {
+        return Ok(None);
+    }
+    let Some(releases) = changeset_releases(backend, &format!("{commit_removed}^"), path)? else {
+        return Ok(None);
+    };
+    if filtered(options, &releases) {
+        return Ok(None);
+    }
+    Ok(Some(UnknownOrigin {
+        name: file_name(path),
+        path: path.to_string(),
+        directory: directory(options, path),
+        commit_removed,
+        removed,
+        releases,
+    }))
+}
+
+/// Turn one add/delete cycle of path into a record, or `None` if it is not reported.
+fn lifetime(
+    options: &Options,
+    backend: &mut dyn Backend,
+    path: &str,
+    interval: Interval,
+) -> Result<Option<Record>> {
+    let Some((created_hash, created_dt)) = interval.added else {
+        let removed = interval.removed.expect("interval without add has a delete");
+        let unknown = unknown_origin(options, backend, path, removed)?;
+        return Ok(unknown.map(Record::UnknownOrigin));
+    };
+    if created_dt >= options.end {
+        return Ok(None);
+    }
+
+    let meta = interval.removed;
+    if let Some((_, deleted_dt)) = meta
+        && deleted_dt < options.start
+    {
+        return Ok(None);
+    }
+
+    let age: Duration = match meta {
+        Some((_, deleted_dt)) => deleted_dt - created_dt,
+        None if options.as_of_end => options.end - created_dt,
+        None => Utc::now() - created_dt,
+    };
+    // truncate to minutes. No need for nanosecond precision.
+    let age = Duration::minutes(age.num_minutes());
+    if age.is_zero() || age < options.min_age {
+        return Ok(None);
+    }
+
+    let Some(releases) = changeset_releases(backend, &created_hash, path)? else {
+        return Ok(None);
+    };
+    if filtered(options, &releases) {
+        return Ok(None);
+    }
+    Ok(Some(Record::Lifetime(ChangesetLifetime {
+        name: file_name(path),
+        commit_added: created_hash,
+        commit_removed: meta.as_ref().map(|(h, _)| h.clone()),
+        created: created_dt,
+        removed: meta.as_ref().map(|(_, dt)| *dt),
+        age,
+        path: path.to_string(),
+        directory: directory(options, path),
+        releases,
+        tag: None,
+    })))
+}
+
+/// Run the analysis, handing each record to `emit` as soon as it is resolved.
+///
+/// Records arrive in path order, not sorted by age.
+pub fn analyze_with(
+    backend: &mut dyn Backend,
+    options: &Options,
+    mut emit: impl FnMut(Record) -> Result<()>,
+) -> Result<()> {
+    if !options.allow_shallow
+        && let Some(shallow) = backend.shallow(&options.branch)?
+    {
+        // Fetching back to the window start only helps for an actual window, not for
+        // pending changesets (`start` is `end`) or all of history
+        let bounded = options.start > DateTime::UNIX_EPOCH && options.start < options.end;
+        return Err(Error::ShallowHistory {
+            depth: shallow.depth,
+            oldest: shallow.oldest,
+            start: bounded.then_some(options.start),
+        });
+    }
+
+    let classifier = Classifier::new(&options.include, &options.exclude);
+    // Every add and delete of a changeset path, gathered in one pass over the history
+    let events = backend.history_events(&options.branch, &options.changeset_dirs, &|path| {
+        classifier.is_candidate(path)
+    })?;
+
+    // A release removes many changesets in the same commit
+    let mut tags: HashMap<String, Option<Tag>> = HashMap::new();
+    for (path, mut path_events) in events {
+        if options.as_of_end {
+            path_events.retain(|e| e.date < options.end);
+        }
+        for interval in history::intervals(path_events) {
+            let Some(mut record) = lifetime(options, backend, &path, interval)? else {
+                continue;
+            };
+            if options.release_tags
+                && let Record::Lifetime(cs) = &mut record
+                && let Some(commit) = &cs.commit_removed
+            {
+                if !tags.contains_key(commit) {
+                    let tag =
+                        backend.first_tag_containing(commit, options.tag_pattern.as_deref())?;
+                    tags.insert(commit.clone(), tag);
+                }
+                cs.tag = tags[commit].clone();
+            }
+            emit(record)?;
+        }
+    }
+    Ok(())
+}
+
+/// Collect all records.
+pub fn analyze(backend: &mut dyn Backend, options: &Options) -> Result<Analysis> {
+    let mut analysis = Analysis {
+        changesets: Vec::new(),
+        unknown_origin: Vec::new(),
+    };
+    analyze_with(backend, options, |record| {
+        match record {
+            Record::Lifetime(cs) => analysis.changesets.push(cs),
+            Record::UnknownOrigin(u) => analysis.unknown_origin.push(u),
+        }
+        Ok(())
+    })?;
+    analysis
+        .changesets
+        .sort_by_key(|cs| std::cmp::Reverse(cs.age));
+    Ok(analysis)
+}
+
+/// [`analyze`] the repository checked out at `dir` with the `git` binary.
+pub fn lifetimes(dir: &str, options: &Options) -> Result<Analysis> {
+    analyze(&mut git::GitCli::new(dir), options)
+}
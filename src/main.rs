@@ -1,175 +1,743 @@
+use changeset_lifetime::backend::Backend;
+use changeset_lifetime::compare::{self, Comparison};
+use changeset_lifetime::config::{self, Config};
+use changeset_lifetime::frontmatter::Bump;
+use changeset_lifetime::git::GitCli;
+#[cfg(feature = "gix")]
+use changeset_lifetime::gitoxide::Gitoxide;
+use changeset_lifetime::glob::Glob;
+use changeset_lifetime::group::{GroupBy, Grouper, PackageGroup};
+use changeset_lifetime::output::{self, OutputFormat, Report};
+use changeset_lifetime::queue::{Queue, Step};
+use changeset_lifetime::stats::{Stat, Summary};
+use changeset_lifetime::survival::Survival;
+use changeset_lifetime::timespec::{Edge, TimeSpec, Tz};
+use changeset_lifetime::tool_config::ToolConfig;
+use changeset_lifetime::trend::{Period, TrendBy, Trender};
+use changeset_lifetime::{Options, Record, Result};
 use chrono::{DateTime, Duration, Utc};
-use clap::Parser;
-use std::collections::HashSet;
-use std::path::Path;
+use clap::{CommandFactory, Parser};
 use std::io::{self, Write};
-use std::process::{Command, Stdio};
+use std::path::PathBuf;
 
 fn eprintln_exit(msg: &str, code: i32) -> ! {
     let _ = writeln!(io::stderr(), "{msg}");
     std::process::exit(code);
 }
 
-fn run_git(dir: &str, args: &[&str]) -> Vec<String> {
-    let output = Command::new("git")
-        .args(args)
-        .current_dir(dir)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::inherit())
-        .output()
-        .unwrap_or_else(|e| eprintln_exit(&format!("failed to run git: {e}"), 1));
+fn parse_duration(s: &str) -> std::result::Result<Duration, String> {
+    let dur = humantime::parse_duration(s).map_err(|e| e.to_string())?;
+    Duration::from_std(dur).map_err(|_| format!("duration {s:?} is too long"))
+}
 
-    if !output.status.success() {
-        eprintln_exit(
-            &format!("git {:?} failed with status {}", args, output.status),
-            1,
-        );
-    }
+fn human(age: Duration) -> humantime::FormattedDuration {
+    humantime::format_duration(age.to_std().unwrap())
+}
 
-    String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect()
-}
-
-struct ChangesetLifetime {
-    name: String,
-    commit_added: String,
-    commit_removed: Option<String>,
-    age: Duration,
-}
-
-/// Oldest add commit for path (first time file was added).
-fn commit_created(dir: &str, branch: &str, path: &str) -> (String, DateTime<Utc>) {
-    let lines = run_git(dir, &[
-        "log",
-        branch,
-        "--diff-filter=A",
-        "--follow",
-        "--format=%H %aI",
-        "--",
-        path,
-    ]);
-    let mut parts = lines[0].split_whitespace();
-    let hash = parts.next().expect("two parts").trim();
-    let ts = parts.next().expect("two parts").trim();
-    match DateTime::parse_from_rfc3339(ts) {
-        Ok(dt) => (hash.to_string(), dt.with_timezone(&Utc)),
-        Err(_) => panic!("failed to parse date from git log"),
-    }
+/// Measure how long changesets stay in a repository before a release removes them
+#[derive(clap::Parser)]
+struct Cli {
+    #[command(flatten)]
+    common: CommonArgs,
+    #[command(subcommand)]
+    command: Command,
 }
 
-/// Newest delete commit for path (last time file was deleted).
-fn commit_deleted(dir: &str, branch: &str, path: &str) -> Option<(String, DateTime<Utc>)> {
-    let lines = run_git(dir, &[
-        "log",
-        branch,
-        "--diff-filter=D",
-        "--follow",
-        "--format=%H %aI",
-        "--",
-        path,
-    ]);
-    if lines.is_empty() {
-        return None;
-    }
-    // newest delete = first line
-    let mut parts = lines[0].split_whitespace();
-    let hash = parts.next().expect("two parts").trim();
-    let ts = parts.next().expect("two parts").trim();
-    match DateTime::parse_from_rfc3339(ts) {
-        Ok(dt) => Some((hash.to_string(), dt.with_timezone(&Utc))),
-        Err(_) => None,
-    }
+// Options shared by every subcommand
+#[derive(clap::Args)]
+struct CommonArgs {
+    #[clap(short, long, global = true, default_value = ".")]
+    dir: String,
+    /// Configuration file with defaults for these options (default: changeset-lifetime.toml at the repository root)
+    #[clap(long, global = true)]
+    config: Option<PathBuf>,
+    /// Branch to analyze (default: baseBranch from the Changesets config, or master)
+    #[clap(long, global = true)]
+    branch: Option<String>,
+    /// Directory holding the changesets, relative to the repository root; repeat for several or use a glob like packages/*/.changeset (default: .changeset)
+    #[clap(long, global = true)]
+    changeset_dir: Vec<String>,
+    /// Output format (default: text)
+    #[clap(long, global = true, value_enum)]
+    format: Option<OutputFormat>,
+    /// Only report changesets whose highest bump is one of these levels
+    #[clap(long, global = true, value_enum, value_delimiter = ',')]
+    bump: Vec<Bump>,
+    /// Glob for files counted as changesets (default `*.md`); patterns without `/` match the file name
+    #[clap(long, global = true)]
+    include: Vec<Glob>,
+    /// Glob for files under the changeset directory to ignore
+    #[clap(long, global = true)]
+    exclude: Vec<Glob>,
+    /// Analyze shallow clones anyway; ages of changesets added before the oldest fetched commit are wrong
+    #[clap(long, global = true)]
+    allow_shallow: bool,
+    /// Timezone for dates without an offset and periods like last-quarter: UTC, local or an offset like +02:00
+    #[clap(long, global = true, default_value = "UTC")]
+    tz: Tz,
+    /// How to read the repository (default: gitoxide when built with the gix feature, otherwise git)
+    #[clap(long, global = true, value_enum)]
+    backend: Option<BackendKind>,
 }
 
-fn parse_duration(s: &str) -> Result<Duration, humantime::DurationError> {
-    let dur = humantime::parse_duration(s)?;
-    Ok(Duration::from_std(dur).unwrap())
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum BackendKind {
+    /// Run the `git` binary
+    Git,
+    /// Read the repository in process, without a `git` binary; needs the gix cargo feature
+    Gitoxide,
 }
 
-#[derive(clap::Parser)]
-struct Args {
-    #[clap(short, long, default_value = ".")]
+/// [`CommonArgs`] with the configuration files applied.
+struct Common {
     dir: String,
-    #[clap(long, default_value = "master")]
+    backend: BackendKind,
     branch: String,
+    changeset_dirs: Vec<String>,
+    format: OutputFormat,
+    bump: Vec<Bump>,
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+    allow_shallow: bool,
+    tz: Tz,
+    /// From the Changesets config.
+    ignore_packages: Vec<Glob>,
+    package_groups: Vec<PackageGroup>,
+}
+
+impl CommonArgs {
+    /// Flags override `changeset-lifetime.toml`, which overrides the Changesets config in the
+    /// changeset directory, which overrides the built-in defaults.
+    fn resolve(self, config: &mut Config) -> Result<Common> {
+        let or_config = |flags: Vec<Glob>, file: &mut Vec<Glob>| match flags.is_empty() {
+            true => std::mem::take(file),
+            false => flags,
+        };
+        let mut changeset_dirs = match self.changeset_dir.is_empty() {
+            true => std::mem::take(&mut config.changeset_dirs),
+            false => self.changeset_dir,
+        };
+        if changeset_dirs.is_empty() {
+            changeset_dirs.push(".changeset".to_string());
+        }
+        // The Changesets config comes from the first listed directory that has one; globs are
+        // not expanded
+        let root = config::repo_root(&self.dir).unwrap_or_else(|| PathBuf::from(&self.dir));
+        let mut tool = None;
+        for changeset_dir in &changeset_dirs {
+            tool = ToolConfig::load(&root.join(changeset_dir))?;
+            if tool.is_some() {
+                break;
+            }
+        }
+        let tool = tool.unwrap_or_default();
+        Ok(Common {
+            branch: self
+                .branch
+                .or(config.branch.take())
+                .or(tool.base_branch)
+                .unwrap_or_else(|| "master".to_string()),
+            dir: self.dir,
+            backend: self.backend.unwrap_or(match cfg!(feature = "gix") {
+                true => BackendKind::Gitoxide,
+                false => BackendKind::Git,
+            }),
+            changeset_dirs,
+            format: self.format.or(config.format).unwrap_or(OutputFormat::Text),
+            bump: self.bump,
+            include: or_config(self.include, &mut config.include),
+            exclude: or_config(self.exclude, &mut config.exclude),
+            allow_shallow: self.allow_shallow || config.allow_shallow.unwrap_or(false),
+            tz: self.tz,
+            ignore_packages: tool.ignore,
+            package_groups: tool.package_groups,
+        })
+    }
+}
+
+impl Common {
+    fn backend(&self) -> Result<Box<dyn Backend>> {
+        Ok(match self.backend {
+            BackendKind::Git => Box::new(GitCli::new(&self.dir)),
+            #[cfg(feature = "gix")]
+            BackendKind::Gitoxide => Box::new(Gitoxide::open(&self.dir)?),
+            #[cfg(not(feature = "gix"))]
+            BackendKind::Gitoxide => unreachable!("rejected in main"),
+        })
+    }
+}
+
+/// The analysis window.
+#[derive(clap::Args)]
+struct Window {
+    /// Date (2025-03-01), timestamp, `90d ago` or a period like last-quarter; defaults to the beginning of history
     #[clap(long)]
-    start: DateTime<Utc>,
+    start: Option<TimeSpec>,
+    /// Same forms as --start, a date or period ends where it ends; defaults to now
     #[clap(long)]
-    end: DateTime<Utc>,
-    #[clap(long="days", default_value = "30days", value_parser = parse_duration)]
-    min_days: Duration,
+    end: Option<TimeSpec>,
+    /// Evaluate the repository as of --end: ignore later adds and deletes and age pending changesets until --end instead of now
+    #[clap(long)]
+    as_of_end: bool,
 }
 
-fn main() {
-    let args = Args::parse();
+#[derive(clap::Args)]
+struct Tags {
+    /// Resolve the first release tag containing each removal commit and report time to tag
+    #[clap(long)]
+    tags: bool,
+    /// Only consider release tags matching this glob, e.g. `@scope/pkg@*` (implies --tags)
+    #[clap(long)]
+    tag_pattern: Option<String>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// List the changesets released or pending in the window with their ages
+    Report {
+        #[command(flatten)]
+        window: Window,
+        /// Only report changesets at least this old (default: 30days)
+        #[clap(long = "days", value_parser = parse_duration)]
+        min_days: Option<Duration>,
+        /// Extra statistics to report alongside the mean age
+        #[clap(long, value_enum, value_delimiter = ',')]
+        stats: Vec<Stat>,
+        /// Report statistics per group instead of listing individual changesets
+        #[clap(long, value_enum)]
+        group_by: Option<GroupBy>,
+        /// Print how many commits a clone needs to cover every reported changeset (e.g. for CI fetch-depth); needs a full clone
+        #[clap(long)]
+        required_depth: bool,
+        #[command(flatten)]
+        tags: Tags,
+        /// Estimate time to release with pending changesets censored at --end (Kaplan–Meier); includes changesets younger than --days
+        #[clap(long)]
+        survival: bool,
+        /// Report the number of pending changesets sampled every day or week from --start to --end; counts changesets younger than --days too
+        #[clap(long, value_enum)]
+        queue: Option<Step>,
+    },
+    /// List the changesets pending now, oldest first
+    Pending {
+        /// Only list changesets at least this old
+        #[clap(long, default_value = "0s", value_parser = parse_duration)]
+        min_age: Duration,
+    },
+    /// Age statistics of the window without the individual changesets
+    Stats {
+        #[command(flatten)]
+        window: Window,
+        /// Only count changesets at least this old (default: 0s); with a baseline window, count every changeset and compare the share older than this instead (default: 30days)
+        #[clap(long = "days", value_parser = parse_duration)]
+        min_days: Option<Duration>,
+        #[clap(long, value_enum, value_delimiter = ',', default_value = "p50,p90")]
+        stats: Vec<Stat>,
+        /// Report statistics per group
+        #[clap(long, value_enum)]
+        group_by: Option<GroupBy>,
+        #[command(flatten)]
+        tags: Tags,
+        /// Estimate time to release with pending changesets censored at --end (Kaplan–Meier)
+        #[clap(long)]
+        survival: bool,
+        /// Start of a baseline window to compare --start..--end against; both windows are evaluated as of their end
+        #[clap(
+            long,
+            requires = "baseline_end",
+            conflicts_with_all = ["group_by", "survival", "tags", "tag_pattern"]
+        )]
+        baseline_start: Option<TimeSpec>,
+        /// End of the baseline window
+        #[clap(long, requires = "baseline_start")]
+        baseline_end: Option<TimeSpec>,
+    },
+    /// Count, mean and median age per week or month of the window
+    Trend {
+        #[command(flatten)]
+        window: Window,
+        #[clap(long, value_enum, default_value = "month")]
+        period: Period,
+        /// Date that puts a changeset into a bucket
+        #[clap(long, value_enum, default_value = "released")]
+        by: TrendBy,
+        /// Only count changesets at least this old
+        #[clap(long = "days", default_value = "0s", value_parser = parse_duration)]
+        min_days: Duration,
+        /// Extra statistics to report per bucket alongside the mean and median
+        #[clap(long, value_enum, value_delimiter = ',')]
+        stats: Vec<Stat>,
+    },
+    /// Exit with status 1 and list the offenders if a pending changeset is too old; tool
+    /// errors exit with status 2
+    Check {
+        /// Pending changesets at least this old fail the check (default: 30days)
+        #[clap(long, value_parser = parse_duration)]
+        max_age: Option<Duration>,
+    },
+    /// Every add/remove cycle of one changeset
+    Show {
+        /// File name (with or without `.md`) or path of the changeset
+        changeset: String,
+        #[command(flatten)]
+        tags: Tags,
+    },
+}
 
-    if args.end <= args.start {
-        eprintln_exit("end must be after start", 1);
+/// What [`run`] analyzes and reports; every subcommand is a preset of it.
+struct Plan {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    /// No start was given; time series begin at the oldest changeset instead of `start`.
+    from_beginning: bool,
+    as_of_end: bool,
+    min_age: Duration,
+    /// File name or path of the one changeset to report, replacing `--include`. Reporting
+    /// nothing is an error.
+    only: Option<String>,
+    stats: Vec<Stat>,
+    group_by: Option<GroupBy>,
+    required_depth: bool,
+    tags: bool,
+    tag_pattern: Option<String>,
+    survival: bool,
+    queue: Option<Step>,
+    trend: Option<(Period, TrendBy)>,
+    list: bool,
+}
+
+impl Plan {
+    fn new(start: DateTime<Utc>, end: DateTime<Utc>, min_age: Duration) -> Plan {
+        Plan {
+            start,
+            end,
+            from_beginning: false,
+            as_of_end: false,
+            min_age,
+            only: None,
+            stats: Vec::new(),
+            group_by: None,
+            required_depth: false,
+            tags: false,
+            tag_pattern: None,
+            survival: false,
+            queue: None,
+            trend: None,
+            list: true,
+        }
     }
 
-    // All paths ever added/deleted under dir (includes deleted files)
-    let files_raw = run_git(&args.dir, &[
-        "log",
-        &args.branch,
-        "--diff-filter=AD",
-        "--name-only",
-        "--pretty=format:",
-        "--",
-        ".changeset",
-    ]);
+    fn window(window: &Window, tz: Tz, min_age: Duration) -> Plan {
+        let now = Utc::now();
+        let start = window
+            .start
+            .as_ref()
+            .map(|s| s.resolve(Edge::Start, tz, now));
+        let end = window
+            .end
+            .as_ref()
+            .map_or(now, |e| e.resolve(Edge::End, tz, now));
+        let start_or_epoch = start.unwrap_or(DateTime::UNIX_EPOCH);
+        if end <= start_or_epoch {
+            eprintln_exit("end must be after start", 1);
+        }
+        Plan {
+            from_beginning: start.is_none(),
+            as_of_end: window.as_of_end,
+            ..Plan::new(start_or_epoch, end, min_age)
+        }
+    }
 
-    let mut changesets = Vec::new();
-    let files: HashSet<String> = files_raw.into_iter().collect();
-    for fp in files {
-        let (created_hash, created_dt) = commit_created(&args.dir, &args.branch, &fp);
+    /// Changesets pending now. Starting the window now skips every changeset that was
+    /// already released.
+    fn pending(min_age: Duration) -> Plan {
+        let now = Utc::now();
+        Plan::new(now, now, min_age)
+    }
 
-        if created_dt > args.end {
-            continue;
+    fn with_tags(self, tags: &Tags) -> Plan {
+        Plan {
+            tags: tags.tags,
+            tag_pattern: tags.tag_pattern.clone(),
+            ..self
         }
+    }
 
-        let meta = commit_deleted(&args.dir, &args.branch, &fp);
-        if let Some((_, deleted_dt)) = meta {
-            if deleted_dt < args.start {
-                continue;
-            }
+    fn options(&self, common: &Common) -> Options {
+        Options {
+            branch: common.branch.clone(),
+            changeset_dirs: common.changeset_dirs.clone(),
+            min_age: self.min_age,
+            include: match &self.only {
+                Some(name) => vec![Glob::new(name)],
+                None => common.include.clone(),
+            },
+            exclude: common.exclude.clone(),
+            bumps: common.bump.clone(),
+            ignore_packages: common.ignore_packages.clone(),
+            allow_shallow: common.allow_shallow,
+            release_tags: self.tags || self.tag_pattern.is_some(),
+            tag_pattern: self.tag_pattern.clone(),
+            as_of_end: self.as_of_end,
+            ..Options::new(self.start, self.end)
         }
+    }
+}
 
-        let age: Duration = match meta {
-            Some((_, deleted_dt)) => {
-                deleted_dt - created_dt
-            }
-            None => {
-                Utc::now() - created_dt
+fn run(common: &Common, plan: &Plan) -> Result<()> {
+    let (start, end) = (plan.start, plan.end);
+    // The survival estimate and the queue need every changeset in the window, including those
+    // released quickly, so the minimum age only limits the rest of the report
+    let all_spans = plan.survival || plan.queue.is_some();
+    let options = Options {
+        min_age: match all_spans {
+            true => Duration::zero(),
+            false => plan.min_age,
+        },
+        ..plan.options(common)
+    };
+    let mut out = io::stdout().lock();
+    // NDJSON records are written as soon as they are resolved, so nothing is collected.
+    let streaming = matches!(common.format, OutputFormat::Ndjson);
+    let mut ages = Vec::new();
+    let mut tag_ages = Vec::new();
+    let mut spans = Vec::new();
+    let mut grouper = plan
+        .group_by
+        .map(|by| Grouper::new(by, common.package_groups.clone()));
+    let mut trender = plan
+        .trend
+        .map(|(period, by)| Trender::new(period, by, common.tz));
+
+    let mut changesets = Vec::new();
+    let mut unknown_origin = Vec::new();
+    let mut oldest_add: Option<(String, DateTime<Utc>)> = None;
+    // Without --start, the queue and trend series begin at the oldest changeset found
+    let mut first_created: Option<DateTime<Utc>> = None;
+    let mut reported = 0;
+    let mut backend = common.backend()?;
+    let shallow = match common.allow_shallow {
+        true => backend.shallow(&common.branch)?,
+        false => None,
+    };
+    if let Some(shallow) = &shallow {
+        eprintln!(
+            "warning: shallow clone with {} commits; changesets added before {} get wrong ages",
+            shallow.depth, shallow.oldest
+        );
+    }
+    changeset_lifetime::analyze_with(&mut *backend, &options, |record| {
+        let changeset = match record {
+            Record::Lifetime(changeset) => changeset,
+            Record::UnknownOrigin(u) => {
+                eprintln!(
+                    "warning: {} was removed in {} but no commit adding it is on {}; reporting it with unknown origin",
+                    u.path, u.commit_removed, common.branch
+                );
+                reported += 1;
+                if streaming && plan.list {
+                    output::write_ndjson_unknown_origin(&mut out, &u)?;
+                } else {
+                    unknown_origin.push(u);
+                }
+                return Ok(());
             }
         };
-        // truncate to minutes. No need for nanosecond precision.
-        let age = Duration::minutes(age.num_minutes());
-        if age.is_zero() || age < args.min_days {
-            continue;
-        }
-
-        
-        let changeset = ChangesetLifetime{
-            name: Path::new(&fp).file_name().unwrap().to_string_lossy().to_string(),
-            commit_added: created_hash,
-            commit_removed: meta.as_ref().map(|(h, _)| h.clone()),
-            age,
-        };
-        changesets.push(changeset);
+        if first_created.is_none_or(|dt| changeset.created < dt) {
+            first_created = Some(changeset.created);
+        }
+        if all_spans {
+            spans.push((changeset.created, changeset.removed));
+        }
+        if changeset.age < plan.min_age {
+            return Ok(());
+        }
+        reported += 1;
+        if oldest_add
+            .as_ref()
+            .is_none_or(|(_, dt)| changeset.created < *dt)
+        {
+            oldest_add = Some((changeset.commit_added.clone(), changeset.created));
+        }
+        ages.push(changeset.age);
+        tag_ages.extend(changeset.time_to_tag());
+        if let Some(grouper) = grouper.as_mut() {
+            grouper.add(&changeset);
+        }
+        if let Some(trender) = trender.as_mut() {
+            trender.add(&changeset);
+        }
+        if streaming {
+            if plan.list {
+                output::write_ndjson_changeset(&mut out, &changeset)?;
+            }
+        } else {
+            changesets.push(changeset);
+        }
+        Ok(())
+    })?;
+
+    if let Some(name) = &plan.only
+        && reported == 0
+    {
+        eprintln_exit(&format!("no changeset named {name}"), 1);
+    }
+
+    if plan.required_depth && shallow.is_some() {
+        // Changesets older than the clone look added in its oldest commit
+        eprintln!("required history depth: unknown in a shallow clone, run it on a full clone");
+    } else if plan.required_depth {
+        match &oldest_add {
+            Some((commit, created)) => eprintln!(
+                "required history depth: {} commits (oldest add commit {commit} from {created})",
+                backend.depth_of(&common.branch, commit)?
+            ),
+            None => eprintln!("required history depth: no changesets reported"),
+        }
+        if !unknown_origin.is_empty() {
+            eprintln!("some add commits are missing, so deeper history may be needed");
+        }
     }
 
+    let series_start = match plan.from_beginning {
+        true => first_created,
+        false => Some(start),
+    };
+    let summary = Summary::from_ages(&ages, &plan.stats);
+    let grouping = grouper.map(|g| g.finish(&plan.stats));
+    let trend = trender.map(|t| t.finish(series_start, end, &plan.stats));
+    let tag_summary = options
+        .release_tags
+        .then(|| Summary::from_ages(&tag_ages, &plan.stats));
+    let survival = plan.survival.then(|| Survival::estimate(&spans, end));
+    let queue = plan
+        .queue
+        .map(|step| Queue::sample(&spans, series_start, end, step, common.tz));
+
     // Sort by age descending
-    changesets.sort_by(|a, b| b.age.cmp(&a.age));
+    changesets.sort_by_key(|cs| std::cmp::Reverse(cs.age));
+
+    let report = Report {
+        list: plan.list,
+        changesets,
+        unknown_origin,
+        grouping,
+        summary,
+        tag_summary,
+        survival,
+        queue,
+        trend,
+    };
+    if streaming {
+        output::write_ndjson_tail(&mut out, &report)?;
+        return Ok(());
+    }
+    output::write_report(&mut out, common.format, &report)?;
+    Ok(())
+}
 
-    let mut avg = Duration::zero();
-    let n = changesets.len();
-    for cs in changesets {
-        avg = avg + cs.age;
-        println!("{} {} - {}  ({})", cs.name, cs.commit_added, cs.commit_removed.unwrap_or("".into()), humantime::format_duration(cs.age.to_std().unwrap()));
+/// Analyze the baseline and the current window with every changeset included, so that
+/// `threshold` only marks which ones count as too old.
+fn run_compare(
+    common: &Common,
+    plan: &Plan,
+    baseline: (DateTime<Utc>, DateTime<Utc>),
+    threshold: Duration,
+) -> Result<()> {
+    let mut backend = common.backend()?;
+    // Every comparison reports the median in addition to the requested statistics
+    let stats = Stat::with_median(&plan.stats);
+    let mut period = |(start, end)| -> Result<compare::Period> {
+        let options = Options {
+            start,
+            end,
+            min_age: Duration::zero(),
+            as_of_end: true,
+            ..plan.options(common)
+        };
+        let analysis = changeset_lifetime::analyze(&mut *backend, &options)?;
+        Ok(compare::Period::new(
+            start,
+            end,
+            &analysis.changesets,
+            threshold,
+            &stats,
+        ))
+    };
+    let comparison = Comparison {
+        threshold,
+        baseline: period(baseline)?,
+        current: period((plan.start, plan.end))?,
+    };
+    output::write_comparison(&mut io::stdout().lock(), common.format, &comparison)?;
+    Ok(())
+}
+
+/// List pending changesets at least `max_age` old and return how many there are.
+fn run_check(common: &Common, max_age: Duration) -> Result<usize> {
+    let options = Plan::pending(max_age).options(common);
+    let analysis = changeset_lifetime::analyze(&mut *common.backend()?, &options)?;
+    if analysis.changesets.is_empty() {
+        eprintln!("ok: no pending changeset is older than {}", human(max_age));
+        return Ok(0);
+    }
+    let violations = analysis.changesets.len();
+    let ages: Vec<Duration> = analysis.changesets.iter().map(|cs| cs.age).collect();
+    let report = Report {
+        list: true,
+        changesets: analysis.changesets,
+        unknown_origin: analysis.unknown_origin,
+        grouping: None,
+        summary: Summary::from_ages(&ages, &[]),
+        tag_summary: None,
+        survival: None,
+        queue: None,
+        trend: None,
+    };
+    output::write_report(&mut io::stdout().lock(), common.format, &report)?;
+    eprintln!(
+        "{violations} pending changesets are older than {}",
+        human(max_age)
+    );
+    Ok(violations)
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if !cfg!(feature = "gix") && matches!(cli.common.backend, Some(BackendKind::Gitoxide)) {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::InvalidValue,
+                "--backend gitoxide needs a build with the gix feature",
+            )
+            .exit();
+    }
+    // `check` keeps status 1 for violations
+    let error_status = match cli.command {
+        Command::Check { .. } => 2,
+        _ => 1,
+    };
+    let config_path = cli
+        .common
+        .config
+        .clone()
+        .or_else(|| config::discover(&cli.common.dir));
+    let mut config = match &config_path {
+        Some(path) => {
+            Config::load(path).unwrap_or_else(|e| eprintln_exit(&e.to_string(), error_status))
+        }
+        None => Config::default(),
+    };
+    let common = &cli
+        .common
+        .resolve(&mut config)
+        .unwrap_or_else(|e| eprintln_exit(&e.to_string(), error_status));
+    let default_threshold = Duration::days(30);
+
+    let result = match &cli.command {
+        Command::Report {
+            window,
+            min_days,
+            stats,
+            group_by,
+            required_depth,
+            tags,
+            survival,
+            queue,
+        } => run(
+            common,
+            &Plan {
+                stats: stats.clone(),
+                group_by: group_by.or(config.group_by),
+                required_depth: *required_depth,
+                survival: *survival,
+                queue: *queue,
+                ..Plan::window(
+                    window,
+                    common.tz,
+                    min_days.or(config.min_age).unwrap_or(default_threshold),
+                )
+                .with_tags(tags)
+            },
+        ),
+        Command::Pending { min_age } => run(common, &Plan::pending(*min_age)),
+        Command::Stats {
+            window,
+            min_days,
+            stats,
+            group_by,
+            tags,
+            survival,
+            baseline_start,
+            baseline_end,
+        } => {
+            let plan = Plan {
+                stats: stats.clone(),
+                group_by: group_by.or(config.group_by),
+                survival: *survival,
+                list: false,
+                ..Plan::window(window, common.tz, min_days.unwrap_or_else(Duration::zero))
+                    .with_tags(tags)
+            };
+            let now = Utc::now();
+            let baseline = baseline_start
+                .as_ref()
+                .zip(baseline_end.as_ref())
+                .map(|(s, e)| {
+                    (
+                        s.resolve(Edge::Start, common.tz, now),
+                        e.resolve(Edge::End, common.tz, now),
+                    )
+                });
+            match baseline {
+                Some((start, end)) if end <= start => {
+                    eprintln_exit("baseline end must be after start", 1)
+                }
+                Some(baseline) => {
+                    let threshold = min_days.or(config.min_age).unwrap_or(default_threshold);
+                    run_compare(common, &plan, baseline, threshold)
+                }
+                None => run(common, &plan),
+            }
+        }
+        Command::Trend {
+            window,
+            period,
+            by,
+            min_days,
+            stats,
+        } => run(
+            common,
+            &Plan {
+                stats: stats.clone(),
+                trend: Some((*period, *by)),
+                list: false,
+                ..Plan::window(window, common.tz, *min_days)
+            },
+        ),
+        Command::Check { max_age } => {
+            let max_age = max_age.or(config.max_age).unwrap_or(default_threshold);
+            match run_check(common, max_age) {
+                Ok(0) => std::process::exit(0),
+                Ok(_) => std::process::exit(1),
+                Err(e) => eprintln_exit(&e.to_string(), 2),
+            }
+        }
+        Command::Show { changeset, tags } => {
+            let name = match changeset.contains('/') || changeset.ends_with(".md") {
+                true => changeset.clone(),
+                false => format!("{changeset}.md"),
+            };
+            run(
+                common,
+                &Plan {
+                    only: Some(name),
+                    ..Plan::new(DateTime::UNIX_EPOCH, Utc::now(), Duration::zero()).with_tags(tags)
+                },
+            )
+        }
+    };
+    if let Err(e) = result {
+        eprintln_exit(&e.to_string(), error_status);
     }
-    let avg = Duration::minutes(avg.num_minutes() / n.max(1) as i64).to_std().unwrap();
-    println!("Total: {} changesets ({})", n, humantime::format_duration(avg));
 }
@@ -0,0 +1,836 @@
+use crate::compare::{self, Comparison};
+use crate::frontmatter::Release;
+use crate::group::Grouping;
+use crate::queue::Queue;
+use crate::stats::Summary;
+use crate::survival::Survival;
+use crate::trend::Trend;
+use crate::{ChangesetLifetime, UnknownOrigin};
+use chrono::{DateTime, Duration, NaiveDate, SecondsFormat, Utc};
+use serde_json::{Map, Value, json};
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable lines
+    Text,
+    /// Single JSON document
+    Json,
+    /// Comma separated values with a header row
+    Csv,
+    /// Tab separated values with a header row
+    Tsv,
+    /// One JSON object per line, streamed unsorted, followed by a summary record
+    Ndjson,
+}
+
+/// Everything produced by one analysis run.
+pub struct Report {
+    /// List individual changesets; off for summary-only output.
+    pub list: bool,
+    pub changesets: Vec<ChangesetLifetime>,
+    pub unknown_origin: Vec<UnknownOrigin>,
+    pub grouping: Option<Grouping>,
+    pub summary: Summary,
+    /// Time to tag statistics, when release tags were resolved.
+    pub tag_summary: Option<Summary>,
+    pub survival: Option<Survival>,
+    /// Pending changeset count over the analysis window.
+    pub queue: Option<Queue>,
+    /// Age statistics per week or month.
+    pub trend: Option<Trend>,
+}
+
+fn timestamp(dt: &DateTime<Utc>) -> String {
+    dt.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn human(age: &Duration) -> humantime::FormattedDuration {
+    humantime::format_duration(age.to_std().unwrap())
+}
+
+pub fn write_report(out: &mut impl Write, format: OutputFormat, report: &Report) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => write_text(out, report),
+        OutputFormat::Json => write_json(out, report),
+        OutputFormat::Csv => write_delimited(out, ',', report),
+        OutputFormat::Tsv => write_delimited(out, '\t', report),
+        OutputFormat::Ndjson => {
+            if report.list {
+                for cs in &report.changesets {
+                    write_ndjson_changeset(out, cs)?;
+                }
+                for u in &report.unknown_origin {
+                    write_ndjson_unknown_origin(out, u)?;
+                }
+            }
+            write_ndjson_tail(out, report)
+        }
+    }
+}
+
+fn write_text(out: &mut impl Write, report: &Report) -> io::Result<()> {
+    match &report.grouping {
+        Some(grouping) => {
+            for group in &grouping.groups {
+                write_text_summary_line(out, &group.key, &group.summary)?;
+            }
+        }
+        None if report.list => {
+            for cs in &report.changesets {
+                write!(
+                    out,
+                    "{} {} - {}  ({})",
+                    cs.name,
+                    cs.commit_added,
+                    cs.commit_removed.as_deref().unwrap_or(""),
+                    human(&cs.age)
+                )?;
+                if let (Some(tag), Some(time_to_tag)) = (&cs.tag, cs.time_to_tag()) {
+                    write!(out, " -> {} ({})", tag.name, human(&time_to_tag))?;
+                }
+                writeln!(out)?;
+            }
+            for u in &report.unknown_origin {
+                writeln!(out, "{} ? - {}  (unknown origin)", u.name, u.commit_removed)?;
+            }
+        }
+        None => {}
+    }
+    let summary = &report.summary;
+    writeln!(
+        out,
+        "Total: {} changesets ({})",
+        summary.count,
+        human(&summary.mean)
+    )?;
+    write_text_stats(out, summary)?;
+    if let Some(summary) = &report.tag_summary {
+        writeln!(
+            out,
+            "Time to tag: {} changesets ({})",
+            summary.count,
+            human(&summary.mean)
+        )?;
+        write_text_stats(out, summary)?;
+    }
+    if let Some(survival) = &report.survival {
+        write_text_survival(out, survival)?;
+    }
+    if let Some(queue) = &report.queue {
+        writeln!(out, "Pending changesets per {}:", queue.step.label())?;
+        for sample in &queue.samples {
+            writeln!(out, "{} {:>6}", sample.date, sample.open)?;
+        }
+    }
+    if let Some(trend) = &report.trend {
+        writeln!(
+            out,
+            "Trend by {} {}:",
+            trend.by.label(),
+            trend.period.label()
+        )?;
+        for bucket in &trend.buckets {
+            write_text_summary_line(out, &bucket.start.to_string(), &bucket.summary)?;
+        }
+    }
+    Ok(())
+}
+
+/// `key: N changesets (mean), stat value, ...` on one line.
+fn write_text_summary_line(out: &mut impl Write, key: &str, summary: &Summary) -> io::Result<()> {
+    write!(
+        out,
+        "{}: {} changesets ({})",
+        key,
+        summary.count,
+        human(&summary.mean)
+    )?;
+    for (stat, value) in &summary.stats {
+        if let Some(age) = value {
+            write!(out, ", {} {}", stat.label(), human(age))?;
+        }
+    }
+    writeln!(out)
+}
+
+fn write_text_survival(out: &mut impl Write, survival: &Survival) -> io::Result<()> {
+    writeln!(
+        out,
+        "Survival: {} released, {} pending censored at {}",
+        survival.released,
+        survival.censored,
+        timestamp(&survival.censored_at)
+    )?;
+    match &survival.median {
+        Some(median) => writeln!(out, "Estimated median time to release: {}", human(median))?,
+        None => writeln!(out, "Estimated median time to release: not reached")?,
+    }
+    writeln!(
+        out,
+        "{:>12} {:>8} {:>8} {:>8} {:>8}",
+        "days", "at_risk", "released", "censored", "survival"
+    )?;
+    for point in &survival.curve {
+        writeln!(
+            out,
+            "{:>12.1} {:>8} {:>8} {:>8} {:>8.3}",
+            point.time.num_minutes() as f64 / (24.0 * 60.0),
+            point.at_risk,
+            point.released,
+            point.censored,
+            point.survival
+        )?;
+    }
+    Ok(())
+}
+
+fn write_text_stats(out: &mut impl Write, summary: &Summary) -> io::Result<()> {
+    for (stat, value) in &summary.stats {
+        match value {
+            Some(age) => writeln!(out, "{}: {}", stat.label(), human(age))?,
+            None => writeln!(out, "{}: -", stat.label())?,
+        }
+    }
+    Ok(())
+}
+
+fn releases_json(releases: &[Release]) -> Value {
+    releases
+        .iter()
+        .map(|r| json!({"package": r.package, "bump": r.bump.label()}))
+        .collect()
+}
+
+fn changeset_json(cs: &ChangesetLifetime) -> Value {
+    json!({
+        "name": cs.name,
+        "path": cs.path,
+        "directory": cs.directory,
+        "commit_added": cs.commit_added,
+        "commit_removed": cs.commit_removed,
+        "created_at": timestamp(&cs.created),
+        "removed_at": cs.removed.as_ref().map(timestamp),
+        "age_seconds": cs.age.num_seconds(),
+        "bump": cs.bump().label(),
+        "releases": releases_json(&cs.releases),
+        "tag": cs.tag.as_ref().map(|t| &t.name),
+        "tagged_at": cs.tag.as_ref().map(|t| timestamp(&t.date)),
+        "time_to_tag_seconds": cs.time_to_tag().map(|d| d.num_seconds()),
+    })
+}
+
+fn unknown_origin_json(u: &UnknownOrigin) -> Value {
+    json!({
+        "name": u.name,
+        "path": u.path,
+        "directory": u.directory,
+        "commit_removed": u.commit_removed,
+        "removed_at": timestamp(&u.removed),
+        "bump": u.bump().label(),
+        "releases": releases_json(&u.releases),
+    })
+}
+
+fn summary_fields(summary: &Summary) -> Map<String, Value> {
+    let mut fields = Map::new();
+    fields.insert("count".to_string(), summary.count.into());
+    fields.insert(
+        "mean_age_seconds".to_string(),
+        summary.mean.num_seconds().into(),
+    );
+    for (stat, value) in &summary.stats {
+        fields.insert(
+            format!("{}_age_seconds", stat.label()),
+            value.map(|age| age.num_seconds()).into(),
+        );
+    }
+    fields
+}
+
+fn summary_json(summary: &Summary) -> Value {
+    Value::Object(summary_fields(summary))
+}
+
+fn group_json(grouping: &Grouping, key: &str, summary: &Summary) -> Value {
+    let mut fields = Map::new();
+    fields.insert("group_by".to_string(), grouping.by.label().into());
+    fields.insert("key".to_string(), key.into());
+    fields.extend(summary_fields(summary));
+    Value::Object(fields)
+}
+
+fn grouping_json(grouping: &Grouping) -> Value {
+    grouping
+        .groups
+        .iter()
+        .map(|g| group_json(grouping, &g.key, &g.summary))
+        .collect()
+}
+
+fn survival_json(survival: &Survival) -> Value {
+    let curve: Vec<Value> = survival
+        .curve
+        .iter()
+        .map(|p| {
+            json!({
+                "time_seconds": p.time.num_seconds(),
+                "at_risk": p.at_risk,
+                "released": p.released,
+                "censored": p.censored,
+                "survival": p.survival,
+            })
+        })
+        .collect();
+    json!({
+        "censored_at": timestamp(&survival.censored_at),
+        "released": survival.released,
+        "censored": survival.censored,
+        "median_seconds": survival.median.map(|d| d.num_seconds()),
+        "curve": curve,
+    })
+}
+
+fn queue_sample_json(step: &str, at: &DateTime<Utc>, open: usize) -> Value {
+    json!({"step": step, "at": timestamp(at), "open": open})
+}
+
+fn trend_bucket_json(trend: &Trend, start: &NaiveDate, summary: &Summary) -> Value {
+    let mut fields = Map::new();
+    fields.insert("period".to_string(), trend.period.label().into());
+    fields.insert("by".to_string(), trend.by.label().into());
+    fields.insert("start".to_string(), start.to_string().into());
+    fields.extend(summary_fields(summary));
+    Value::Object(fields)
+}
+
+fn write_json(out: &mut impl Write, report: &Report) -> io::Result<()> {
+    let mut fields = Map::new();
+    if report.list {
+        fields.insert(
+            "changesets".to_string(),
+            report.changesets.iter().map(changeset_json).collect(),
+        );
+        fields.insert(
+            "unknown_origin".to_string(),
+            report
+                .unknown_origin
+                .iter()
+                .map(unknown_origin_json)
+                .collect(),
+        );
+    }
+    if let Some(grouping) = &report.grouping {
+        fields.insert("groups".to_string(), grouping_json(grouping));
+    }
+    fields.insert("summary".to_string(), summary_json(&report.summary));
+    if let Some(summary) = &report.tag_summary {
+        fields.insert("time_to_tag_summary".to_string(), summary_json(summary));
+    }
+    if let Some(survival) = &report.survival {
+        fields.insert("survival".to_string(), survival_json(survival));
+    }
+    if let Some(queue) = &report.queue {
+        let samples = queue
+            .samples
+            .iter()
+            .map(|s| queue_sample_json(queue.step.label(), &s.at, s.open))
+            .collect();
+        fields.insert("queue".to_string(), samples);
+    }
+    if let Some(trend) = &report.trend {
+        let buckets = trend
+            .buckets
+            .iter()
+            .map(|b| trend_bucket_json(trend, &b.start, &b.summary))
+            .collect();
+        fields.insert("trend".to_string(), buckets);
+    }
+    writeln!(out, "{}", Value::Object(fields))
+}
+
+/// Prefix an object with a `type` discriminator so NDJSON consumers can tell records apart.
+fn tagged(kind: &str, value: Value) -> Value {
+    let mut fields = Map::new();
+    fields.insert("type".to_string(), kind.into());
+    if let Value::Object(rest) = value {
+        fields.extend(rest);
+    }
+    Value::Object(fields)
+}
+
+pub fn write_ndjson_changeset(out: &mut impl Write, cs: &ChangesetLifetime) -> io::Result<()> {
+    writeln!(out, "{}", tagged("changeset", changeset_json(cs)))
+}
+
+pub fn write_ndjson_unknown_origin(out: &mut impl Write, u: &UnknownOrigin) -> io::Result<()> {
+    writeln!(out, "{}", tagged("unknown_origin", unknown_origin_json(u)))
+}
+
+/// Records written once the scan is complete: one per group, the time to tag summary, the
+/// survival estimate, one per queue sample, one per trend bucket, then the summary.
+/// `report.changesets` is not used.
+pub fn write_ndjson_tail(out: &mut impl Write, report: &Report) -> io::Result<()> {
+    if let Some(grouping) = &report.grouping {
+        for group in &grouping.groups {
+            let record = group_json(grouping, &group.key, &group.summary);
+            writeln!(out, "{}", tagged("group", record))?;
+        }
+    }
+    if let Some(tag_summary) = &report.tag_summary {
+        let record = tagged("time_to_tag_summary", summary_json(tag_summary));
+        writeln!(out, "{record}")?;
+    }
+    if let Some(survival) = &report.survival {
+        writeln!(out, "{}", tagged("survival", survival_json(survival)))?;
+    }
+    if let Some(queue) = &report.queue {
+        for s in &queue.samples {
+            let record = queue_sample_json(queue.step.label(), &s.at, s.open);
+            writeln!(out, "{}", tagged("queue", record))?;
+        }
+    }
+    if let Some(trend) = &report.trend {
+        for b in &trend.buckets {
+            let record = trend_bucket_json(trend, &b.start, &b.summary);
+            writeln!(out, "{}", tagged("trend", record))?;
+        }
+    }
+    writeln!(out, "{}", tagged("summary", summary_json(&report.summary)))
+}
+
+const DELIMITED_HEADER: [&str; 13] = [
+    "name",
+    "path",
+    "directory",
+    "commit_added",
+    "commit_removed",
+    "added_at",
+    "removed_at",
+    "age_minutes",
+    "packages",
+    "bump",
+    "tag",
+    "tagged_at",
+    "time_to_tag_minutes",
+];
+
+/// Quote a field if it contains the delimiter, a quote or a line break.
+fn delimited_field(field: &str, delim: char) -> String {
+    if field.contains([delim, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_row(out: &mut impl Write, delim: char, fields: &[&str]) -> io::Result<()> {
+    let row: Vec<String> = fields.iter().map(|f| delimited_field(f, delim)).collect();
+    writeln!(out, "{}", row.join(&delim.to_string()))
+}
+
+fn summary_row(summary: &Summary) -> Vec<String> {
+    let mut row = vec![
+        summary.count.to_string(),
+        summary.mean.num_minutes().to_string(),
+    ];
+    for (_, value) in &summary.stats {
+        row.push(
+            value
+                .map(|age| age.num_minutes().to_string())
+                .unwrap_or_default(),
+        );
+    }
+    row
+}
+
+/// One row per key with its summary; `key_column` heads the key column.
+fn write_summaries_delimited<'a>(
+    out: &mut impl Write,
+    delim: char,
+    key_column: &str,
+    rows: impl IntoIterator<Item = (String, &'a Summary)>,
+) -> io::Result<()> {
+    let mut rows = rows.into_iter().peekable();
+    let mut header = vec![
+        key_column.to_string(),
+        "count".to_string(),
+        "mean_minutes".to_string(),
+    ];
+    if let Some((_, first)) = rows.peek() {
+        for (stat, _) in &first.stats {
+            header.push(format!("{}_minutes", stat.label()));
+        }
+    }
+    write_row(
+        out,
+        delim,
+        &header.iter().map(String::as_str).collect::<Vec<_>>(),
+    )?;
+    for (key, summary) in rows {
+        let mut row = vec![key];
+        row.extend(summary_row(summary));
+        write_row(
+            out,
+            delim,
+            &row.iter().map(String::as_str).collect::<Vec<_>>(),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_groups_delimited(
+    out: &mut impl Write,
+    delim: char,
+    grouping: &Grouping,
+) -> io::Result<()> {
+    let rows = grouping.groups.iter().map(|g| (g.key.clone(), &g.summary));
+    write_summaries_delimited(out, delim, grouping.by.label(), rows)
+}
+
+fn write_changesets_delimited(
+    out: &mut impl Write,
+    delim: char,
+    report: &Report,
+) -> io::Result<()> {
+    let changesets = &report.changesets;
+    write_row(out, delim, &DELIMITED_HEADER)?;
+    for cs in changesets {
+        let added_at = timestamp(&cs.created);
+        let removed_at = cs.removed.as_ref().map(timestamp).unwrap_or_default();
+        let age_minutes = cs.age.num_minutes().to_string();
+        let packages: Vec<&str> = cs.releases.iter().map(|r| r.package.as_str()).collect();
+        let tagged_at = cs
+            .tag
+            .as_ref()
+            .map(|t| timestamp(&t.date))
+            .unwrap_or_default();
+        let time_to_tag = cs
+            .time_to_tag()
+            .map(|d| d.num_minutes().to_string())
+            .unwrap_or_default();
+        write_row(
+            out,
+            delim,
+            &[
+                &cs.name,
+                &cs.path,
+                &cs.directory,
+                &cs.commit_added,
+                cs.commit_removed.as_deref().unwrap_or(""),
+                &added_at,
+                &removed_at,
+                &age_minutes,
+                &packages.join(";"),
+                cs.bump().label(),
+                cs.tag.as_ref().map_or("", |t| t.name.as_str()),
+                &tagged_at,
+                &time_to_tag,
+            ],
+        )?;
+    }
+    // Unknown origin: no add commit, add date or age
+    for u in &report.unknown_origin {
+        let removed_at = timestamp(&u.removed);
+        let packages: Vec<&str> = u.releases.iter().map(|r| r.package.as_str()).collect();
+        write_row(
+            out,
+            delim,
+            &[
+                &u.name,
+                &u.path,
+                &u.directory,
+                "",
+                &u.commit_removed,
+                "",
+                &removed_at,
+                "",
+                &packages.join(";"),
+                u.bump().label(),
+                "",
+                "",
+                "",
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Tables after the first are separated by a blank line.
+fn start_table(out: &mut impl Write, first: &mut bool) -> io::Result<()> {
+    if !std::mem::take(first) {
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// With `--group-by` the first table holds one row per group instead of one row per changeset.
+fn write_delimited(out: &mut impl Write, delim: char, report: &Report) -> io::Result<()> {
+    let mut first = true;
+    match &report.grouping {
+        Some(grouping) => {
+            start_table(out, &mut first)?;
+            write_groups_delimited(out, delim, grouping)?;
+        }
+        None if report.list => {
+            start_table(out, &mut first)?;
+            write_changesets_delimited(out, delim, report)?;
+        }
+        None => {}
+    }
+    if let Some(survival) = &report.survival {
+        start_table(out, &mut first)?;
+        write_row(
+            out,
+            delim,
+            &[
+                "time_minutes",
+                "at_risk",
+                "released",
+                "censored",
+                "survival",
+            ],
+        )?;
+        for p in &survival.curve {
+            write_row(
+                out,
+                delim,
+                &[
+                    &p.time.num_minutes().to_string(),
+                    &p.at_risk.to_string(),
+                    &p.released.to_string(),
+                    &p.censored.to_string(),
+                    &format!("{:.6}", p.survival),
+                ],
+            )?;
+        }
+    }
+    if let Some(queue) = &report.queue {
+        start_table(out, &mut first)?;
+        write_row(out, delim, &["step", "at", "open"])?;
+        for s in &queue.samples {
+            write_row(
+                out,
+                delim,
+                &[queue.step.label(), &timestamp(&s.at), &s.open.to_string()],
+            )?;
+        }
+    }
+    if let Some(trend) = &report.trend {
+        start_table(out, &mut first)?;
+        let column = format!("{}_{}", trend.by.label(), trend.period.label());
+        let rows = trend
+            .buckets
+            .iter()
+            .map(|b| (b.start.to_string(), &b.summary));
+        write_summaries_delimited(out, delim, &column, rows)?;
+    }
+    // Without a listing the statistics table is the point of the output.
+    let summary = &report.summary;
+    if report.list && summary.stats.is_empty() {
+        return Ok(());
+    }
+    start_table(out, &mut first)?;
+    write_row(out, delim, &["stat", "age_minutes"])?;
+    write_row(out, delim, &["count", &summary.count.to_string()])?;
+    write_row(
+        out,
+        delim,
+        &["mean", &summary.mean.num_minutes().to_string()],
+    )?;
+    for (stat, value) in &summary.stats {
+        let minutes = value.map(|age| age.num_minutes().to_string());
+        write_row(
+            out,
+            delim,
+            &[stat.label(), minutes.as_deref().unwrap_or("")],
+        )?;
+    }
+    Ok(())
+}
+
+/// Age with a leading sign, for deltas.
+fn signed_human(delta: &Duration) -> String {
+    let sign = if *delta < Duration::zero() { "-" } else { "+" };
+    format!("{sign}{}", human(&delta.abs()))
+}
+
+/// `(label, baseline, current)` for every compared age statistic, `None` where undefined.
+fn compared_ages(
+    comparison: &Comparison,
+) -> Vec<(&'static str, Option<Duration>, Option<Duration>)> {
+    let (baseline, current) = (&comparison.baseline.summary, &comparison.current.summary);
+    let mut rows = vec![("mean", Some(baseline.mean), Some(current.mean))];
+    for ((stat, b), (_, c)) in baseline.stats.iter().zip(&current.stats) {
+        rows.push((stat.label(), *b, *c));
+    }
+    rows
+}
+
+pub fn write_comparison(
+    out: &mut impl Write,
+    format: OutputFormat,
+    comparison: &Comparison,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => write_comparison_text(out, comparison),
+        OutputFormat::Json => writeln!(out, "{}", comparison_json(comparison)),
+        OutputFormat::Ndjson => {
+            writeln!(out, "{}", tagged("comparison", comparison_json(comparison)))
+        }
+        OutputFormat::Csv => write_comparison_delimited(out, ',', comparison),
+        OutputFormat::Tsv => write_comparison_delimited(out, '\t', comparison),
+    }
+}
+
+fn write_comparison_text(out: &mut impl Write, comparison: &Comparison) -> io::Result<()> {
+    let (baseline, current) = (&comparison.baseline, &comparison.current);
+    for (label, period) in [("Baseline", baseline), ("Current", current)] {
+        writeln!(
+            out,
+            "{label}: {} - {}",
+            timestamp(&period.start),
+            timestamp(&period.end)
+        )?;
+    }
+    writeln!(
+        out,
+        "count: {} -> {} ({:+})",
+        baseline.summary.count,
+        current.summary.count,
+        current.summary.count as i64 - baseline.summary.count as i64
+    )?;
+    for (label, b, c) in compared_ages(comparison) {
+        match (b, c) {
+            (Some(b), Some(c)) => writeln!(
+                out,
+                "{label}: {} -> {} ({})",
+                human(&b),
+                human(&c),
+                signed_human(&(c - b))
+            )?,
+            _ => writeln!(out, "{label}: -")?,
+        }
+    }
+    writeln!(
+        out,
+        "older than {}: {:.1}% -> {:.1}% ({:+.1} points)",
+        human(&comparison.threshold),
+        baseline.over_threshold_share() * 100.0,
+        current.over_threshold_share() * 100.0,
+        (current.over_threshold_share() - baseline.over_threshold_share()) * 100.0
+    )
+}
+
+fn period_json(period: &compare::Period) -> Value {
+    let mut fields = Map::new();
+    fields.insert("start".to_string(), timestamp(&period.start).into());
+    fields.insert("end".to_string(), timestamp(&period.end).into());
+    fields.extend(summary_fields(&period.summary));
+    fields.insert("over_threshold".to_string(), period.over_threshold.into());
+    fields.insert(
+        "over_threshold_share".to_string(),
+        period.over_threshold_share().into(),
+    );
+    Value::Object(fields)
+}
+
+fn comparison_json(comparison: &Comparison) -> Value {
+    let (baseline, current) = (&comparison.baseline, &comparison.current);
+    let mut delta = Map::new();
+    delta.insert(
+        "count".to_string(),
+        (current.summary.count as i64 - baseline.summary.count as i64).into(),
+    );
+    for (label, b, c) in compared_ages(comparison) {
+        let seconds = b.zip(c).map(|(b, c)| (c - b).num_seconds());
+        delta.insert(format!("{label}_age_seconds"), seconds.into());
+    }
+    delta.insert(
+        "over_threshold".to_string(),
+        (current.over_threshold as i64 - baseline.over_threshold as i64).into(),
+    );
+    delta.insert(
+        "over_threshold_share".to_string(),
+        (current.over_threshold_share() - baseline.over_threshold_share()).into(),
+    );
+    json!({
+        "threshold_seconds": comparison.threshold.num_seconds(),
+        "baseline": period_json(baseline),
+        "current": period_json(current),
+        "delta": delta,
+    })
+}
+
+fn write_comparison_delimited(
+    out: &mut impl Write,
+    delim: char,
+    comparison: &Comparison,
+) -> io::Result<()> {
+    let (baseline, current) = (&comparison.baseline, &comparison.current);
+    write_row(out, delim, &["metric", "baseline", "current", "delta"])?;
+    let counts = [
+        ("count", baseline.summary.count, current.summary.count),
+        (
+            "over_threshold",
+            baseline.over_threshold,
+            current.over_threshold,
+        ),
+    ];
+    for (label, b, c) in counts {
+        write_row(
+            out,
+            delim,
+            &[
+                label,
+                &b.to_string(),
+                &c.to_string(),
+                &(c as i64 - b as i64).to_string(),
+            ],
+        )?;
+    }
+    for (label, b, c) in compared_ages(comparison) {
+        let minutes = |age: Option<Duration>| age.map(|a| a.num_minutes().to_string());
+        let delta = b.zip(c).map(|(b, c)| (c - b).num_minutes().to_string());
+        write_row(
+            out,
+            delim,
+            &[
+                &format!("{label}_minutes"),
+                minutes(b).as_deref().unwrap_or(""),
+                minutes(c).as_deref().unwrap_or(""),
+                delta.as_deref().unwrap_or(""),
+            ],
+        )?;
+    }
+    let (b, c) = (
+        baseline.over_threshold_share(),
+        current.over_threshold_share(),
+    );
+    write_row(
+        out,
+        delim,
+        &[
+            "over_threshold_share",
+            &format!("{b:.6}"),
+            &format!("{c:.6}"),
+            &format!("{:.6}", c - b),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delimited_field_quotes_only_when_needed() {
+        assert_eq!(delimited_field("red-fox.md", ','), "red-fox.md");
+        assert_eq!(delimited_field("a,b", ','), "\"a,b\"");
+        assert_eq!(delimited_field("a,b", '\t'), "a,b");
+        assert_eq!(delimited_field("a\tb", '\t'), "\"a\tb\"");
+        assert_eq!(delimited_field("say \"hi\"", ','), "\"say \"\"hi\"\"\"");
+        assert_eq!(delimited_field("two\nlines", ','), "\"two\nlines\"");
+        assert_eq!(delimited_field("crlf\r\n", '\t'), "\"crlf\r\n\"");
+    }
+}
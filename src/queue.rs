@@ -0,0 +1,117 @@
+use crate::timespec::Tz;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+/// Sampling step of the queue time series.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum Step {
+    Day,
+    Week,
+}
+
+impl Step {
+    pub fn label(self) -> &'static str {
+        match self {
+            Step::Day => "day",
+            Step::Week => "week",
+        }
+    }
+
+    fn duration(self) -> Duration {
+        match self {
+            Step::Day => Duration::days(1),
+            Step::Week => Duration::weeks(1),
+        }
+    }
+}
+
+/// Number of changesets added but not yet removed at the start of a day.
+pub struct QueueSample {
+    /// The day in the timezone of the analysis.
+    pub date: NaiveDate,
+    /// Midnight of `date`.
+    pub at: DateTime<Utc>,
+    pub open: usize,
+}
+
+/// Pending changeset count sampled every `step` from `start` up to `end`, which is excluded.
+pub struct Queue {
+    pub step: Step,
+    pub samples: Vec<QueueSample>,
+}
+
+impl Queue {
+    /// `spans` holds the add and removal date of every changeset. Samples are taken at
+    /// midnight in `tz`, starting with the day `start` falls on; without a `start` there are
+    /// no samples.
+    pub fn sample(
+        spans: &[(DateTime<Utc>, Option<DateTime<Utc>>)],
+        start: Option<DateTime<Utc>>,
+        end: DateTime<Utc>,
+        step: Step,
+        tz: Tz,
+    ) -> Queue {
+        let mut samples = Vec::new();
+        let Some(start) = start else {
+            return Queue { step, samples };
+        };
+        let mut date = tz.date(start);
+        // Each sample is a new midnight rather than the previous one plus a step, which
+        // would drift across DST changes
+        let mut at = tz.midnight(date);
+        while at < end {
+            let open = spans
+                .iter()
+                .filter(|(created, removed)| *created <= at && removed.is_none_or(|r| r > at))
+                .count();
+            samples.push(QueueSample { date, at, open });
+            date += step.duration();
+            at = tz.midnight(date);
+        }
+        Queue { step, samples }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    #[test]
+    fn samples_at_midnight_in_timezone() {
+        let spans = [
+            (at("2024-01-01T16:00:00Z"), Some(at("2024-01-02T16:00:00Z"))),
+            (at("2024-01-02T12:00:00Z"), None),
+        ];
+        let tz: Tz = "+09:00".parse().unwrap();
+        let queue = Queue::sample(
+            &spans,
+            Some(at("2024-01-01T15:00:00Z")),
+            at("2024-01-04T15:00:00Z"),
+            Step::Day,
+            tz,
+        );
+        let samples: Vec<_> = queue
+            .samples
+            .iter()
+            .map(|s| (s.date.to_string(), s.at, s.open))
+            .collect();
+        assert_eq!(
+            samples,
+            [
+                ("2024-01-02".to_string(), at("2024-01-01T15:00:00Z"), 0),
+                ("2024-01-03".to_string(), at("2024-01-02T15:00:00Z"), 2),
+                ("2024-01-04".to_string(), at("2024-01-03T15:00:00Z"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_samples_without_start() {
+        let spans = [(at("2024-01-01T16:00:00Z"), None)];
+        let queue = Queue::sample(&spans, None, at("2024-01-04T00:00:00Z"), Step::Day, Tz::Utc);
+        assert!(queue.samples.is_empty());
+    }
+}
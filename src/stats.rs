@@ -0,0 +1,153 @@
+use chrono::Duration;
+
+/// Additional aggregate statistics that can be requested with `--stats`.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Stat {
+    Min,
+    Max,
+    Stddev,
+    P50,
+    P75,
+    P90,
+    P95,
+    P99,
+}
+
+impl Stat {
+    pub fn label(self) -> &'static str {
+        match self {
+            Stat::Min => "min",
+            Stat::Max => "max",
+            Stat::Stddev => "stddev",
+            Stat::P50 => "p50",
+            Stat::P75 => "p75",
+            Stat::P90 => "p90",
+            Stat::P95 => "p95",
+            Stat::P99 => "p99",
+        }
+    }
+
+    /// `stats` with the median in front unless it is already requested, for reports that
+    /// always show it.
+    pub fn with_median(stats: &[Stat]) -> Vec<Stat> {
+        let mut stats = stats.to_vec();
+        if !stats.contains(&Stat::P50) {
+            stats.insert(0, Stat::P50);
+        }
+        stats
+    }
+
+    /// `sorted` must be in ascending order.
+    fn compute(self, sorted: &[Duration], mean: Duration) -> Option<Duration> {
+        let n = sorted.len();
+        if n == 0 {
+            return None;
+        }
+        let value = match self {
+            Stat::Min => sorted[0],
+            Stat::Max => sorted[n - 1],
+            Stat::Stddev => {
+                let mean = mean.num_seconds() as f64;
+                let var = sorted
+                    .iter()
+                    .map(|age| (age.num_seconds() as f64 - mean).powi(2))
+                    .sum::<f64>()
+                    / n as f64;
+                Duration::seconds(var.sqrt() as i64)
+            }
+            Stat::P50 => percentile(sorted, 50),
+            Stat::P75 => percentile(sorted, 75),
+            Stat::P90 => percentile(sorted, 90),
+            Stat::P95 => percentile(sorted, 95),
+            Stat::P99 => percentile(sorted, 99),
+        };
+        Some(Duration::minutes(value.num_minutes()))
+    }
+}
+
+/// Nearest-rank percentile of a non-empty ascending slice.
+fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+pub struct Summary {
+    pub count: usize,
+    pub mean: Duration,
+    pub stats: Vec<(Stat, Option<Duration>)>,
+}
+
+impl Summary {
+    pub fn from_ages(ages: &[Duration], stats: &[Stat]) -> Summary {
+        let n = ages.len();
+        let total = ages.iter().fold(Duration::zero(), |acc, age| acc + *age);
+        let mean = Duration::minutes(total.num_minutes() / n.max(1) as i64);
+        let mut sorted = ages.to_vec();
+        sorted.sort();
+        Summary {
+            count: n,
+            mean,
+            stats: stats
+                .iter()
+                .map(|stat| (*stat, stat.compute(&sorted, mean)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(values: &[i64]) -> Vec<Duration> {
+        values.iter().map(|d| Duration::days(*d)).collect()
+    }
+
+    #[test]
+    fn percentile_of_single_value() {
+        let sorted = days(&[7]);
+        for p in [1, 50, 99, 100] {
+            assert_eq!(percentile(&sorted, p), Duration::days(7));
+        }
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let sorted = days(&[1, 2, 3, 4]);
+        assert_eq!(percentile(&sorted, 25), Duration::days(1));
+        assert_eq!(percentile(&sorted, 26), Duration::days(2));
+        assert_eq!(percentile(&sorted, 50), Duration::days(2));
+        assert_eq!(percentile(&sorted, 75), Duration::days(3));
+        assert_eq!(percentile(&sorted, 100), Duration::days(4));
+    }
+
+    #[test]
+    fn high_percentile_of_few_values_is_the_maximum() {
+        let sorted = days(&[1, 2, 3, 4, 5]);
+        assert_eq!(percentile(&sorted, 90), Duration::days(5));
+        assert_eq!(percentile(&sorted, 99), Duration::days(5));
+    }
+
+    #[test]
+    fn with_median_adds_p50_once() {
+        assert!(Stat::with_median(&[Stat::Max]) == [Stat::P50, Stat::Max]);
+        assert!(Stat::with_median(&[Stat::Max, Stat::P50]) == [Stat::Max, Stat::P50]);
+        assert!(Stat::with_median(&[]) == [Stat::P50]);
+    }
+
+    #[test]
+    fn summary_of_no_ages() {
+        let summary = Summary::from_ages(&[], &[Stat::P50, Stat::Min]);
+        assert_eq!(summary.count, 0);
+        assert_eq!(summary.mean, Duration::zero());
+        assert!(summary.stats.iter().all(|(_, value)| value.is_none()));
+    }
+
+    #[test]
+    fn summary_sorts_ages() {
+        let summary = Summary::from_ages(&days(&[9, 1, 5]), &[Stat::Min, Stat::Max, Stat::P50]);
+        let values: Vec<_> = summary.stats.iter().map(|(_, v)| v.unwrap()).collect();
+        assert_eq!(values, days(&[1, 9, 5]));
+        assert_eq!(summary.mean, Duration::days(5));
+    }
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+
+/// One row of the Kaplan–Meier table.
+pub struct SurvivalPoint {
+    pub time: Duration,
+    /// Changesets still pending just before `time`.
+    pub at_risk: usize,
+    pub released: usize,
+    pub censored: usize,
+    /// Estimated share of changesets still pending after `time`.
+    pub survival: f64,
+}
+
+/// Kaplan–Meier estimate of time to release. Changesets still pending at the end of the
+/// window are right-censored there instead of being aged until now.
+pub struct Survival {
+    pub censored_at: DateTime<Utc>,
+    pub released: usize,
+    pub censored: usize,
+    /// Smallest time at which the estimated survival drops to 50% or below; `None` if more
+    /// than half are still estimated to be pending at the last release.
+    pub median: Option<Duration>,
+    pub curve: Vec<SurvivalPoint>,
+}
+
+impl Survival {
+    /// `spans` holds the add and removal date of every changeset.
+    pub fn estimate(
+        spans: &[(DateTime<Utc>, Option<DateTime<Utc>>)],
+        end: DateTime<Utc>,
+    ) -> Survival {
+        // (released, censored) per duration in minutes
+        let mut counts: BTreeMap<i64, (usize, usize)> = BTreeMap::new();
+        for &(created, removed) in spans {
+            let (until, released) = match removed {
+                Some(removed) if removed <= end => (removed, true),
+                _ => (end, false),
+            };
+            let entry = counts
+                .entry((until - created).num_minutes().max(0))
+                .or_default();
+            match released {
+                true => entry.0 += 1,
+                false => entry.1 += 1,
+            }
+        }
+
+        let mut at_risk: usize = counts.values().map(|(r, c)| r + c).sum();
+        let mut survival = 1.0;
+        let mut median = None;
+        let mut curve = Vec::new();
+        for (&minutes, &(released, censored)) in &counts {
+            if released > 0 {
+                survival *= 1.0 - released as f64 / at_risk as f64;
+            }
+            let time = Duration::minutes(minutes);
+            if median.is_none() && survival <= 0.5 {
+                median = Some(time);
+            }
+            curve.push(SurvivalPoint {
+                time,
+                at_risk,
+                released,
+                censored,
+                survival,
+            });
+            at_risk -= released + censored;
+        }
+
+        Survival {
+            censored_at: end,
+            released: counts.values().map(|(r, _)| r).sum(),
+            censored: counts.values().map(|(_, c)| c).sum(),
+            median,
+            curve,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn releases_tied_with_censoring_count_first() {
+        let spans = [
+            (day(1), Some(day(11))),
+            (day(6), None),
+            (day(1), Some(day(15))),
+        ];
+        let survival = Survival::estimate(&spans, day(16));
+        let first = &survival.curve[0];
+        assert_eq!(first.time, Duration::days(10));
+        assert_eq!((first.at_risk, first.released, first.censored), (3, 1, 1));
+        assert!((first.survival - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(survival.curve[1].at_risk, 1);
+        assert_eq!(survival.curve[1].survival, 0.0);
+        assert_eq!(survival.median, Some(Duration::days(14)));
+    }
+
+    #[test]
+    fn median_not_reached() {
+        let spans = [
+            (day(1), Some(day(6))),
+            (day(1), None),
+            (day(2), None),
+            (day(3), None),
+        ];
+        let survival = Survival::estimate(&spans, day(20));
+        assert_eq!((survival.released, survival.censored), (1, 3));
+        assert_eq!(survival.curve[0].survival, 0.75);
+        assert_eq!(survival.median, None);
+    }
+
+    #[test]
+    fn removed_after_end_is_censored_at_end() {
+        let spans = [(day(1), Some(day(20))), (day(5), Some(day(10)))];
+        let survival = Survival::estimate(&spans, day(11));
+        assert_eq!(survival.censored_at, day(11));
+        assert_eq!((survival.released, survival.censored), (1, 1));
+        let times: Vec<_> = survival
+            .curve
+            .iter()
+            .map(|p| (p.time, p.released, p.censored))
+            .collect();
+        assert_eq!(
+            times,
+            [(Duration::days(5), 1, 0), (Duration::days(10), 0, 1)]
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, TimeZone,
+    Utc,
+};
+use std::str::FromStr;
+
+/// Timezone in which dates without an offset and calendar periods are interpreted.
+#[derive(Clone, Copy)]
+pub enum Tz {
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl Tz {
+    fn to_utc(self, local: NaiveDateTime) -> DateTime<Utc> {
+        match self {
+            Tz::Utc => Utc.from_utc_datetime(&local),
+            // A time skipped by a DST change is moved past the gap
+            Tz::Local => Local
+                .from_local_datetime(&local)
+                .earliest()
+                .or_else(|| {
+                    Local
+                        .from_local_datetime(&(local + Duration::hours(1)))
+                        .earliest()
+                })
+                .map_or_else(|| Utc.from_utc_datetime(&local), |dt| dt.to_utc()),
+            Tz::Fixed(offset) => (local - offset).and_utc(),
+        }
+    }
+
+    /// Calendar date of `instant` in this timezone.
+    pub fn date(self, instant: DateTime<Utc>) -> NaiveDate {
+        match self {
+            Tz::Utc => instant.date_naive(),
+            Tz::Local => instant.with_timezone(&Local).date_naive(),
+            Tz::Fixed(offset) => instant.with_timezone(&offset).date_naive(),
+        }
+    }
+
+    /// Start of `date` in this timezone.
+    pub fn midnight(self, date: NaiveDate) -> DateTime<Utc> {
+        self.to_utc(date.and_hms_opt(0, 0, 0).expect("midnight exists"))
+    }
+}
+
+impl FromStr for Tz {
+    type Err = String;
+
+    /// `UTC`, `local` or a fixed offset such as `+02:00`, `-0530` or `+2`.
+    fn from_str(s: &str) -> Result<Tz, String> {
+        let invalid =
+            || format!("invalid timezone {s:?}, expected UTC, local or an offset like +02:00");
+        match s.to_ascii_lowercase().as_str() {
+            "utc" | "z" => return Ok(Tz::Utc),
+            "local" => return Ok(Tz::Local),
+            _ => {}
+        }
+        let (sign, rest) = match s.as_bytes().first() {
+            Some(b'+') => (1, &s[1..]),
+            Some(b'-') => (-1, &s[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if rest.len() == 4 => rest.split_at(2),
+            None => (rest, "0"),
+        };
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Tz::Fixed)
+            .ok_or_else(invalid)
+    }
+}
+
+/// Calendar unit of a named period such as `last-quarter`.
+#[derive(Clone, Copy)]
+pub enum Unit {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Unit {
+    /// First day of the period containing `date`.
+    fn start(self, date: NaiveDate) -> NaiveDate {
+        let first_of = |month| NaiveDate::from_ymd_opt(date.year(), month, 1).expect("valid month");
+        match self {
+            Unit::Day => date,
+            Unit::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+            Unit::Month => first_of(date.month()),
+            Unit::Quarter => first_of(date.month0() / 3 * 3 + 1),
+            Unit::Year => first_of(1),
+        }
+    }
+
+    /// First day of the period `n` periods after the one starting at `start`, or before it
+    /// for negative `n`.
+    fn shift(self, start: NaiveDate, n: i32) -> NaiveDate {
+        let months = |m: i32| match m >= 0 {
+            true => start + Months::new(m as u32),
+            false => start - Months::new(m.unsigned_abs()),
+        };
+        match self {
+            Unit::Day => start + Duration::days(n.into()),
+            Unit::Week => start + Duration::weeks(n.into()),
+            Unit::Month => months(n),
+            Unit::Quarter => months(3 * n),
+            Unit::Year => months(12 * n),
+        }
+    }
+}
+
+/// Which end of the window a [`TimeSpec`] bounds. Dates and named periods cover a span of
+/// time: a start bound resolves to its beginning and an end bound to its end.
+#[derive(Clone, Copy)]
+pub enum Edge {
+    Start,
+    End,
+}
+
+/// A window bound as written on the command line, resolved with [`TimeSpec::resolve`].
+///
+/// Accepts RFC 3339 timestamps, dates and times without an offset (`2025-03-01`,
+/// `2025-03-01T12:00`), `now`, humantime durations followed by `ago` (`90d ago`) and named
+/// periods: `today`, `yesterday` and `this-` or `last-` followed by `week`, `month`,
+/// `quarter` or `year`.
+#[derive(Clone)]
+pub enum TimeSpec {
+    Instant(DateTime<Utc>),
+    /// Date and time without an offset.
+    Local(NaiveDateTime),
+    Day(NaiveDate),
+    Now,
+    Ago(Duration),
+    /// The period `back` units before the current one.
+    Period {
+        unit: Unit,
+        back: i32,
+    },
+}
+
+impl TimeSpec {
+    pub fn resolve(&self, edge: Edge, tz: Tz, now: DateTime<Utc>) -> DateTime<Utc> {
+        let span = |unit: Unit, start: NaiveDate| {
+            let day = match edge {
+                Edge::Start => start,
+                Edge::End => unit.shift(start, 1),
+            };
+            tz.midnight(day)
+        };
+        match self {
+            TimeSpec::Instant(instant) => *instant,
+            TimeSpec::Local(local) => tz.to_utc(*local),
+            TimeSpec::Day(date) => span(Unit::Day, *date),
+            TimeSpec::Now => now,
+            // Parsing rejects durations reaching past the earliest representable time
+            TimeSpec::Ago(duration) => now
+                .checked_sub_signed(*duration)
+                .unwrap_or(DateTime::<Utc>::MIN_UTC),
+            TimeSpec::Period { unit, back } => {
+                let current = unit.start(tz.date(now));
+                span(*unit, unit.shift(current, -back))
+            }
+        }
+    }
+}
+
+impl FromStr for TimeSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TimeSpec, String> {
+        let s = s.trim();
+        let named = s.to_ascii_lowercase().replace([' ', '_'], "-");
+        let period = |unit, back| Ok(TimeSpec::Period { unit, back });
+        match named.as_str() {
+            "now" => return Ok(TimeSpec::Now),
+            "today" => return period(Unit::Day, 0),
+            "yesterday" => return period(Unit::Day, 1),
+            _ => {}
+        }
+        if let Some((which, unit)) = named.split_once('-') {
+            let back = match which {
+                "this" => Some(0),
+                "last" => Some(1),
+                _ => None,
+            };
+            let unit = match unit {
+                "week" => Some(Unit::Week),
+                "month" => Some(Unit::Month),
+                "quarter" => Some(Unit::Quarter),
+                "year" => Some(Unit::Year),
+                _ => None,
+            };
+            if let (Some(back), Some(unit)) = (back, unit) {
+                return period(unit, back);
+            }
+        }
+        if let Some(duration) = s.strip_suffix("ago") {
+            let duration = humantime::parse_duration(duration.trim())
+                .map_err(|e| format!("invalid duration in {s:?}: {e}"))?;
+            return Duration::from_std(duration)
+                .ok()
+                .filter(|d| Utc::now().checked_sub_signed(*d).is_some())
+                .map(TimeSpec::Ago)
+                .ok_or_else(|| format!("duration in {s:?} is too long"));
+        }
+        if let Ok(instant) = DateTime::parse_from_rfc3339(s) {
+            return Ok(TimeSpec::Instant(instant.to_utc()));
+        }
+        for format in [
+            "%Y-%m-%dT%H:%M:%S",
+            "%Y-%m-%d %H:%M:%S",
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%d %H:%M",
+        ] {
+            if let Ok(local) = NaiveDateTime::parse_from_str(s, format) {
+                return Ok(TimeSpec::Local(local));
+            }
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(TimeSpec::Day(date));
+        }
+        Err(format!(
+            "invalid time {s:?}, expected e.g. 2025-03-01, 2025-03-01T12:00:00Z, now, 90d ago or last-quarter"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    fn resolve(spec: &str, edge: Edge, tz: &str, now: &str) -> DateTime<Utc> {
+        let spec: TimeSpec = spec.parse().unwrap();
+        spec.resolve(edge, tz.parse().unwrap(), at(now))
+    }
+
+    #[test]
+    fn last_quarter() {
+        let now = "2025-05-14T10:00:00Z";
+        assert_eq!(
+            resolve("last-quarter", Edge::Start, "UTC", now),
+            at("2025-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            resolve("last-quarter", Edge::End, "UTC", now),
+            at("2025-04-01T00:00:00Z")
+        );
+        assert_eq!(
+            resolve("last quarter", Edge::Start, "UTC", "2025-02-01T00:00:00Z"),
+            at("2024-10-01T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn this_week_starts_on_monday() {
+        let now = "2025-05-14T10:00:00Z";
+        assert_eq!(
+            resolve("this-week", Edge::Start, "UTC", now),
+            at("2025-05-12T00:00:00Z")
+        );
+        assert_eq!(
+            resolve("this-week", Edge::End, "UTC", now),
+            at("2025-05-19T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn periods_follow_the_timezone() {
+        let now = "2025-05-14T22:00:00Z";
+        assert_eq!(
+            resolve("today", Edge::Start, "+09:00", now),
+            at("2025-05-14T15:00:00Z")
+        );
+        assert_eq!(
+            resolve("yesterday", Edge::End, "UTC", now),
+            at("2025-05-14T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn date_edges() {
+        let now = "2025-05-14T10:00:00Z";
+        assert_eq!(
+            resolve("2025-03-01", Edge::Start, "UTC", now),
+            at("2025-03-01T00:00:00Z")
+        );
+        assert_eq!(
+            resolve("2025-03-01", Edge::End, "UTC", now),
+            at("2025-03-02T00:00:00Z")
+        );
+        assert_eq!(
+            resolve("2025-03-01T12:00", Edge::End, "UTC", now),
+            at("2025-03-01T12:00:00Z")
+        );
+        assert_eq!(
+            resolve("2025-03-01T12:00:00+02:00", Edge::End, "-2", now),
+            at("2025-03-01T10:00:00Z")
+        );
+    }
+
+    #[test]
+    fn offsets() {
+        let now = "2025-05-14T10:00:00Z";
+        for tz in ["+0530", "+05:30"] {
+            assert_eq!(
+                resolve("2025-03-01", Edge::Start, tz, now),
+                at("2025-02-28T18:30:00Z")
+            );
+        }
+        assert_eq!(
+            resolve("2025-03-01", Edge::Start, "-2", now),
+            at("2025-03-01T02:00:00Z")
+        );
+        assert_eq!(
+            resolve("2025-03-01 12:00", Edge::Start, "-2", now),
+            at("2025-03-01T14:00:00Z")
+        );
+        for tz in ["0530", "+5x", "+25", ""] {
+            assert!(tz.parse::<Tz>().is_err(), "{tz:?}");
+        }
+    }
+
+    #[test]
+    fn ago() {
+        let now = "2025-05-14T10:00:00Z";
+        assert_eq!(
+            resolve("90d ago", Edge::Start, "UTC", now),
+            at("2025-02-13T10:00:00Z")
+        );
+        assert_eq!(resolve("now", Edge::Start, "UTC", now), at(now));
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        for s in [
+            "100000000years ago",
+            "soon ago",
+            "2025-13-01",
+            "next-week",
+            "",
+        ] {
+            assert!(s.parse::<TimeSpec>().is_err(), "{s:?}");
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::error::{Error, Result};
+use crate::glob::Glob;
+use crate::group::PackageGroup;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Name of the Changesets tool's own configuration in the changeset directory.
+pub const FILE_NAME: &str = "config.json";
+
+/// Settings read from the Changesets tool's `config.json`.
+#[derive(Default)]
+pub struct ToolConfig {
+    /// `baseBranch`, the branch releases are made from.
+    pub base_branch: Option<String>,
+    /// `ignore`: packages that are never released, as names or globs.
+    pub ignore: Vec<Glob>,
+    /// `fixed` groups followed by `linked` groups.
+    pub package_groups: Vec<PackageGroup>,
+}
+
+impl ToolConfig {
+    /// Read `config.json` from the changeset directory `dir`, or `None` if it has none.
+    pub fn load(dir: &Path) -> Result<Option<ToolConfig>> {
+        let path = dir.join(FILE_NAME);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(Error::ConfigRead {
+                    path: path.display().to_string(),
+                    error,
+                });
+            }
+        };
+        let file: File = serde_json::from_str(&text).map_err(|e| {
+            // The line is reported separately
+            let message = e.to_string();
+            let position = format!(" at line {} column {}", e.line(), e.column());
+            Error::Config {
+                path: path.display().to_string(),
+                line: e.line(),
+                message: message
+                    .strip_suffix(&position)
+                    .unwrap_or(&message)
+                    .to_string(),
+            }
+        })?;
+        let mut package_groups = Vec::new();
+        for (setting, groups) in [("fixed", file.fixed), ("linked", file.linked)] {
+            for members in groups {
+                package_groups.push(PackageGroup {
+                    name: format!("{setting}: {}", members.join(", ")),
+                    members: members.iter().map(|m| Glob::full(m)).collect(),
+                });
+            }
+        }
+        Ok(Some(ToolConfig {
+            base_branch: file.base_branch,
+            ignore: file.ignore.iter().map(|p| Glob::full(p)).collect(),
+            package_groups,
+        }))
+    }
+}
+
+/// The settings of `config.json` used here; the Changesets tool reads many more.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", expecting = "an object")]
+struct File {
+    base_branch: Option<String>,
+    #[serde(default)]
+    ignore: Vec<String>,
+    #[serde(default)]
+    fixed: Vec<Vec<String>>,
+    #[serde(default)]
+    linked: Vec<Vec<String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Loads `text` as `config.json` from a directory unique to `test`.
+    fn load(test: &str, text: &str) -> Result<Option<ToolConfig>> {
+        let dir =
+            std::env::temp_dir().join(format!("changeset-lifetime-{test}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(FILE_NAME), text).unwrap();
+        let config = ToolConfig::load(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        config
+    }
+
+    #[test]
+    fn reads_branch_ignore_and_groups() {
+        let text = r#"{
+            "$schema": "https://unpkg.com/@changesets/config/schema.json",
+            "baseBranch": "main",
+            "ignore": ["@scope/*"],
+            "fixed": [["a", "b"]],
+            "linked": [["c"]],
+            "access": "public"
+        }"#;
+        let config = load("settings", text).unwrap().unwrap();
+        assert_eq!(config.base_branch.as_deref(), Some("main"));
+        assert!(config.ignore[0].matches("@scope/pkg"));
+        let names: Vec<_> = config.package_groups.iter().map(|g| &g.name).collect();
+        assert_eq!(names, ["fixed: a, b", "linked: c"]);
+    }
+
+    #[test]
+    fn missing_file_is_none() {
+        let dir = std::env::temp_dir().join("changeset-lifetime-tool-config-missing");
+        assert!(ToolConfig::load(&dir).unwrap().is_none());
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = load(
+            "error-line",
+            "{\n  \"baseBranch\": null,\n  \"ignore\": \"a\"\n}",
+        )
+        .err();
+        let Some(Error::Config { line, message, .. }) = error else {
+            panic!("expected a config error");
+        };
+        assert_eq!(line, 3);
+        assert!(!message.contains("line"), "{message}");
+        assert!(matches!(
+            load("not-an-object", "[]"),
+            Err(Error::Config { line: 1, .. })
+        ));
+    }
+}
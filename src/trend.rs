@@ -0,0 +1,164 @@
+use crate::ChangesetLifetime;
+use crate::stats::{Stat, Summary};
+use crate::timespec::Tz;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use std::collections::BTreeMap;
+
+/// Width of a trend bucket.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum Period {
+    /// ISO weeks starting on Monday
+    Week,
+    /// Calendar months
+    Month,
+}
+
+impl Period {
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::Week => "week",
+            Period::Month => "month",
+        }
+    }
+
+    /// First day of the bucket containing `date`.
+    fn bucket(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+            Period::Month => date.with_day(1).expect("every month has a first day"),
+        }
+    }
+
+    fn next(self, bucket: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week => bucket + Duration::weeks(1),
+            Period::Month => bucket + Months::new(1),
+        }
+    }
+}
+
+/// Which date of a changeset decides its bucket.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum TrendBy {
+    /// Removal date; pending changesets are left out
+    Released,
+    /// Add date
+    Created,
+}
+
+impl TrendBy {
+    pub fn label(self) -> &'static str {
+        match self {
+            TrendBy::Released => "released",
+            TrendBy::Created => "created",
+        }
+    }
+
+    fn date(self, cs: &ChangesetLifetime) -> Option<DateTime<Utc>> {
+        match self {
+            TrendBy::Released => cs.removed,
+            TrendBy::Created => Some(cs.created),
+        }
+    }
+}
+
+pub struct Bucket {
+    /// First day of the bucket.
+    pub start: NaiveDate,
+    pub summary: Summary,
+}
+
+pub struct Trend {
+    pub period: Period,
+    pub by: TrendBy,
+    pub buckets: Vec<Bucket>,
+}
+
+/// Collects ages per week or month while changesets are resolved.
+pub struct Trender {
+    period: Period,
+    by: TrendBy,
+    tz: Tz,
+    ages: BTreeMap<NaiveDate, Vec<Duration>>,
+}
+
+impl Trender {
+    /// Buckets start at midnight in `tz`.
+    pub fn new(period: Period, by: TrendBy, tz: Tz) -> Trender {
+        Trender {
+            period,
+            by,
+            tz,
+            ages: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, cs: &ChangesetLifetime) {
+        if let Some(date) = self.by.date(cs) {
+            let bucket = self.period.bucket(self.tz.date(date));
+            self.ages.entry(bucket).or_default().push(cs.age);
+        }
+    }
+
+    /// One bucket per period from `start` up to `end`, which is excluded, empty ones included
+    /// so gaps show up; without a `start` there are no buckets. Every bucket reports its
+    /// median in addition to the requested statistics.
+    pub fn finish(
+        mut self,
+        start: Option<DateTime<Utc>>,
+        end: DateTime<Utc>,
+        stats: &[Stat],
+    ) -> Trend {
+        let stats = Stat::with_median(stats);
+        let mut buckets = Vec::new();
+        if let Some(start) = start {
+            let last = self
+                .period
+                .bucket(self.tz.date(end - Duration::nanoseconds(1)));
+            let mut bucket = self.period.bucket(self.tz.date(start));
+            while bucket <= last {
+                let ages = self.ages.remove(&bucket).unwrap_or_default();
+                buckets.push(Bucket {
+                    start: bucket,
+                    summary: Summary::from_ages(&ages, &stats),
+                });
+                bucket = self.period.next(bucket);
+            }
+        }
+        Trend {
+            period: self.period,
+            by: self.by,
+            buckets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    #[test]
+    fn empty_buckets_cover_the_window() {
+        let trend = Trender::new(Period::Month, TrendBy::Released, Tz::Utc).finish(
+            Some(at("2024-01-15T00:00:00Z")),
+            at("2024-03-01T00:00:00Z"),
+            &[],
+        );
+        let starts: Vec<_> = trend.buckets.iter().map(|b| b.start.to_string()).collect();
+        assert_eq!(starts, ["2024-01-01", "2024-02-01"]);
+    }
+
+    #[test]
+    fn no_buckets_without_start() {
+        let trend = Trender::new(Period::Week, TrendBy::Created, Tz::Utc).finish(
+            None,
+            at("2024-03-01T00:00:00Z"),
+            &[],
+        );
+        assert!(trend.buckets.is_empty());
+    }
+}